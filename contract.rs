@@ -3,13 +3,46 @@ imports - the one that looks interesting is State structure, that consists of, a
 collateral and counter_offer
 */
 
+use std::collections::BTreeMap;
+
 use cosmwasm_std::{
-    entry_point, to_binary, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    coins, entry_point, to_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, Fraction, MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    SubMsgResult, Uint128, Uint256, WasmMsg,
 };
 
+use cw2::{get_contract_version, set_contract_version};
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, CONFIG};
+use crate::msg::{
+    BreakEvenResponse, ComplianceQueryMsg, ConfigResponse, Cw20ExecuteMsg, EscrowResponse,
+    ExecuteMsg, ExecuteResult, ExportRecord, ExportResponse, HookExecuteMsg, InfoResponse,
+    InstantiateMsg, IsBlockedResponse, OptionStatus, OracleQueryMsg, PriceResponse, QueryMsg,
+    QuoteResponse, RouterExecuteMsg, SimulateExecuteResponse, StateView,
+};
+use crate::state::{
+    BurnBeneficiary, BurnMode, LastAction, PendingRelease, Receipt, Settlement, State,
+    ALLOWED_DENOMS, CONFIG, DENOM_ALIASES, EXECUTING, LAST_ACTION, MIN_COLLATERAL, PENDING_ADMIN,
+    PENDING_RELEASE, RECEIPTS, RESCIND,
+};
+use crate::validate;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:simple-option";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// roughly a year of 6 second blocks
+pub const MAX_EXPIRY_BLOCKS: u64 = 5_256_000;
+
+// characters allowed in the label and memo annotations
+pub const MAX_LABEL_LENGTH: usize = 128;
+
+// reply id of the exercise_hook notification
+pub const EXERCISE_HOOK_REPLY_ID: u64 = 1;
+
+// reply id of the transfer of the CW20 counter_offer
+pub const CW20_TRANSFER_REPLY_ID: u64 = 2;
 
 #[entry_point]
 pub fn instantiate(
@@ -23,6 +56,8 @@ pub fn instantiate(
     - pub expires: u64,
     below condition validates if 'expires' attribute is not lower than current block height 
     - if so, it will return an error
+    the shortest possible option expires at height + 1: exercisable in the instantiation block only,
+    expired (so burnable) from the next one on - matching is_expired, which counts expires itself as expired
     */
     if msg.expires <= env.block.height {
         return Err(ContractError::OptionExpired {
@@ -31,37 +66,416 @@ pub fn instantiate(
     }
 
     /*
-    state declaration - both creator and owner are set as sender, collateral of option set to info.funds,
-    counter_offer as an attribute of Instantiate message and expires that has been validated above
+    options must live at least min_lifetime_blocks (1 by default), so a fat-fingered expiry does not create
+    an option that expires essentially immediately
+    */
+    let lifetime = msg.expires.saturating_sub(env.block.height);
+    let min = msg.min_lifetime_blocks.unwrap_or(1);
+    if lifetime < min {
+        return Err(ContractError::LifetimeTooShort { min });
+    }
+
+    /*
+    options must expire within MAX_EXPIRY_BLOCKS, otherwise collateral could be locked practically forever
+    */
+    if lifetime > MAX_EXPIRY_BLOCKS {
+        return Err(ContractError::ExpiryTooFar {
+            max: MAX_EXPIRY_BLOCKS,
+        });
+    }
+
+    /* zero-amount coins would otherwise be echoed into the bank messages of execute/burn */
+    validate::no_zero_amounts(&info.funds)?;
+    validate::no_zero_amounts(&msg.counter_offer)?;
+    validate::no_zero_amounts(&msg.extension_fee)?;
+    validate::no_zero_amounts(&msg.burn_reward)?;
+    validate::no_zero_amounts(&msg.tolerance)?;
+
+    /* every denom is demanded once, so the amounts in responses and attributes read unambiguously */
+    validate::no_duplicate_denoms(&msg.counter_offer)?;
+    validate::no_duplicate_denoms(&msg.extension_fee)?;
+    validate::no_duplicate_denoms(&msg.tolerance)?;
+
+    /* with an allowlist, both sides of the option must be written in allowed denoms only */
+    validate::all_allowed(&info.funds, &msg.allowed_denoms)?;
+    validate::all_allowed(&msg.counter_offer, &msg.allowed_denoms)?;
+
+    /* optionally catch copy-pasted IBC denoms with a broken hash */
+    if msg.validate_ibc_denoms {
+        validate::ibc_denoms(&info.funds)?;
+        validate::ibc_denoms(&msg.counter_offer)?;
+    }
+
+    /* free text annotations are kept short, they are stored with the option */
+    for (field, text) in [("label", &msg.label), ("memo", &msg.memo)] {
+        if text.as_ref().map_or(0, |t| t.chars().count()) > MAX_LABEL_LENGTH {
+            return Err(ContractError::LabelTooLong {
+                field: field.to_string(),
+                max: MAX_LABEL_LENGTH,
+            });
+        }
+    }
+
+    /* a zero ratio would make a demanded denom free when paying in another one */
+    if let Some((from, to, _)) = msg.denom_ratios.iter().find(|(_, _, r)| r.is_zero()) {
+        return Err(ContractError::ZeroRatio {
+            from: from.clone(),
+            to: to.clone(),
+        });
+    }
+
+    /* the keeper reward on burn is paid out of the collateral, so it has to be covered by it */
+    if subtract_coins(&info.funds, &msg.burn_reward).is_none() {
+        return Err(ContractError::BurnRewardExceedsCollateral {});
+    }
+
+    /* every denom with a configured minimum has to be posted at least in that amount */
+    assert_min_collateral(&msg.min_collateral, &info.funds)?;
+
+    /* the creator always keeps part of the counter_offer */
+    if u128::from(msg.holder_rebate_bps) >= BPS_DENOMINATOR {
+        return Err(ContractError::RebateTooHigh {
+            bps: msg.holder_rebate_bps,
+        });
+    }
+    if u128::from(msg.holder_rebate_bps) + u128::from(msg.referral_bps) >= BPS_DENOMINATOR {
+        return Err(ContractError::ReferralTooHigh {
+            bps: msg.referral_bps,
+        });
+    }
+
+    /* a decaying counter_offer is only defined for a single denom, floored above zero */
+    validate_decay(
+        msg.decay_bps_per_block,
+        &msg.counter_offer,
+        msg.min_counter_offer,
+    )?;
+
+    /* a collar clamps the due counter_offer, so no denom may be floored above its cap */
+    let no_bounds = vec![];
+    let floor = msg.floor.as_ref().unwrap_or(&no_bounds);
+    let cap = msg.cap.as_ref().unwrap_or(&no_bounds);
+    validate::no_zero_amounts(floor)?;
+    validate::no_zero_amounts(cap)?;
+    validate::no_duplicate_denoms(floor)?;
+    validate::no_duplicate_denoms(cap)?;
+    for coin in floor {
+        if cap
+            .iter()
+            .any(|cap| cap.denom == coin.denom && cap.amount < coin.amount)
+        {
+            return Err(ContractError::InvalidCollar {
+                denom: coin.denom.clone(),
+            });
+        }
+    }
+
+    /*
+    with strict_denoms set, none of the collateral denoms may also be demanded in the counter_offer
+    - otherwise the two BankMsg::Send in execute move the same denom in both directions
+    */
+    if msg.strict_denoms {
+        if let Some(coin) = info
+            .funds
+            .iter()
+            .find(|c| msg.counter_offer.iter().any(|o| o.denom == c.denom))
+        {
+            return Err(ContractError::OverlappingDenoms {
+                denom: coin.denom.clone(),
+            });
+        }
+    }
+
+    /*
+    with reject_degenerate set, an option swapping the very same coins both ways is rejected as a mistake
+    */
+    if msg.reject_degenerate && coins_equal_unordered(&info.funds, &msg.counter_offer) {
+        return Err(ContractError::DegenerateOption {});
+    }
+
+    /*
+    optional whitelist of transfer recipients - every entry is validated upfront,
+    an empty list is stored as None meaning transfers are unrestricted
+    */
+    let transfer_whitelist = msg
+        .transfer_whitelist
+        .filter(|list| !list.is_empty())
+        .map(|list| {
+            list.iter()
+                .map(|addr| validate_addr(deps.api, addr))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?;
+
+    /*
+    cash settlement pays out of the collateral, so it has to be a single coin,
+    and the oracle address is validated before it is stored
+    */
+    if let Settlement::CashSettled { oracle, .. } = &msg.settlement {
+        validate_addr(deps.api, oracle.as_str())?;
+        if info.funds.len() != 1 || msg.nft_contract.is_some() {
+            return Err(ContractError::CashSettledCollateral {});
+        }
+    }
+
+    let nft_contract = msg
+        .nft_contract
+        .map(|contract| validate_addr(deps.api, &contract))
+        .transpose()?;
+
+    // an NFT is released on execution, a settlement delay could only hold back the coins next to it
+    if nft_contract.is_some() && msg.settlement_delay_blocks > 0 {
+        return Err(ContractError::DelayedNftCollateral {});
+    }
+
+    // a cash settled payout is sent on execution, it is never held back
+    if msg.settlement != Settlement::Physical && msg.settlement_delay_blocks > 0 {
+        return Err(ContractError::DelayedCashSettlement {});
+    }
+
+    let admin = msg
+        .admin
+        .map(|admin| validate_addr(deps.api, &admin))
+        .transpose()?;
+
+    /*
+    a creator preferring a CW20 over the native counter_offer needs a router to swap it
+    */
+    let receive_as = msg
+        .receive_as
+        .map(|token| validate_addr(deps.api, &token))
+        .transpose()?;
+    let router = msg
+        .router
+        .map(|router| validate_addr(deps.api, &router))
+        .transpose()?;
+    if receive_as.is_some() && router.is_none() {
+        return Err(ContractError::MissingRouter {});
+    }
+
+    let compliance_contract = msg
+        .compliance_contract
+        .map(|contract| validate_addr(deps.api, &contract))
+        .transpose()?;
+
+    let exercise_hook = msg
+        .exercise_hook
+        .map(|hook| validate_addr(deps.api, &hook))
+        .transpose()?;
+
+    /* payees split the whole counter_offer between them, paid natively */
+    let payout_splits = msg
+        .payout_splits
+        .iter()
+        .map(|(payee, bps)| Ok((validate_addr(deps.api, payee)?, *bps)))
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    if !payout_splits.is_empty() {
+        let total: u128 = payout_splits.iter().map(|(_, bps)| u128::from(*bps)).sum();
+        if total != BPS_DENOMINATOR
+            || payout_splits.iter().any(|(_, bps)| *bps == 0)
+            || receive_as.is_some()
+        {
+            return Err(ContractError::InvalidSplits {});
+        }
+    }
+
+    /*
+    co-owners split the coin collateral, so they are limited to plain physically settled options whose
+    collateral is released on execution. The creator cannot be one of them, the option would still look
+    untransferred to the creator-only actions
+    */
+    let owners = msg
+        .owners
+        .iter()
+        .map(|(owner, bps)| Ok((validate_addr(deps.api, owner)?, *bps)))
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    if !owners.is_empty() {
+        let total: u128 = owners.iter().map(|(_, bps)| u128::from(*bps)).sum();
+        if total != BPS_DENOMINATOR
+            || owners.iter().any(|(_, bps)| *bps == 0)
+            || owners.iter().any(|(owner, _)| *owner == info.sender)
+            || msg.settlement != Settlement::Physical
+            || nft_contract.is_some()
+            || msg.settlement_delay_blocks > 0
+        {
+            return Err(ContractError::InvalidOwners {});
+        }
+    }
+
+    /* the CW20 part of the counter_offer is a token contract and a non-zero amount */
+    let cw20_counter_offer = msg
+        .cw20_counter_offer
+        .map(|(token, amount)| {
+            if amount.is_zero() {
+                return Err(ContractError::ZeroAmountCoin { denom: token });
+            }
+            Ok((validate_addr(deps.api, &token)?, amount))
+        })
+        .transpose()?;
+
+    /*
+    state declaration - both creator and owner are set as sender (owner being the first co-owner, if any),
+    collateral of option set to info.funds,
+    counter_offer as an attribute of Instantiate message and expires that has been validated above.
+    Both coin vectors are normalized, so the same set of coins is always stored the same way
     */
     let state = State {
         creator: info.sender.clone(),
-        owner: info.sender.clone(),
-        collateral: info.funds,
-        counter_offer: msg.counter_offer,
+        owner: owners
+            .first()
+            .map(|(owner, _)| owner.clone())
+            .unwrap_or_else(|| info.sender.clone()),
+        collateral: normalize_coins(info.funds),
+        counter_offer: normalize_coins(msg.counter_offer),
         expires: msg.expires,
+        transfer_whitelist,
+        settlement: msg.settlement,
+        burn_beneficiary: msg.burn_beneficiary,
+        burn_mode: msg.burn_mode,
+        approved: None,
+        admin,
+        nft_contract,
+        nft_collateral: None,
+        escrow: vec![],
+        decay_bps_per_block: msg.decay_bps_per_block,
+        decay_start: env.block.height,
+        min_counter_offer: msg.min_counter_offer,
+        floor: msg.floor,
+        cap: msg.cap,
+        extension_fee: msg.extension_fee,
+        receive_as,
+        router,
+        denom_ratios: msg.denom_ratios,
+        burn_reward: msg.burn_reward,
+        split_sends: msg.split_sends,
+        max_submsgs: msg.max_submsgs,
+        compliance_contract,
+        exercise_hook,
+        validate_ibc_denoms: msg.validate_ibc_denoms,
+        early_withdraw_window: msg.early_withdraw_window,
+        cw20_counter_offer,
+        holder_rebate_bps: msg.holder_rebate_bps,
+        referral_bps: msg.referral_bps,
+        tolerance: msg.tolerance,
+        settlement_delay_blocks: msg.settlement_delay_blocks,
+        payout_splits,
+        auto_burn_on_expired: msg.auto_burn_on_expired,
+        owners,
+        allow_creator_exercise: msg.allow_creator_exercise.unwrap_or(true),
+        paused: false,
+        renounced: false,
+        label: msg.label,
+        memo: msg.memo,
     };
 
-    /* save new state to storage */
-    CONFIG.save(deps.storage, &state)?;
+    /* save new state to storage, along with the contract version */
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+    ALLOWED_DENOMS
+        .save(deps.storage, &msg.allowed_denoms)
+        .map_err(ContractError::storage_save)?;
+    DENOM_ALIASES
+        .save(deps.storage, &msg.denom_aliases)
+        .map_err(ContractError::storage_save)?;
+    MIN_COLLATERAL
+        .save(deps.storage, &msg.min_collateral)
+        .map_err(ContractError::storage_save)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)
+        .map_err(ContractError::storage_save)?;
 
-    Ok(Response::default())
+    /* the created option (with computed fields such as decay_start) is returned as data, saving a query */
+    Ok(Response::new().set_data(to_binary(&state)?))
 }
 
 #[entry_point]
 pub fn execute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let sender = info.sender.clone();
+    let height = env.block.height;
+    let res = dispatch(deps.branch(), env, info, msg)?;
+
+    /*
+    lightweight audit trail - LAST_ACTION lives outside of CONFIG, so it survives execute and burn
+    */
+    let action = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "action")
+        .map(|attr| attr.value.clone())
+        .unwrap_or_default();
+    LAST_ACTION
+        .save(
+            deps.storage,
+            &LastAction {
+                action,
+                height,
+                sender,
+            },
+        )
+        .map_err(ContractError::storage_save)?;
+    Ok(res)
+}
+
+fn dispatch(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     /*
-    three execute message enums matched and their associated functions
+    execute message enums matched and their associated functions
     */
     match msg {
         ExecuteMsg::Transfer { recipient } => execute_transfer(deps, env, info, recipient),
-        ExecuteMsg::Execute {} => execute_execute(deps, env, info),
+        ExecuteMsg::Execute {
+            max_extra,
+            deadline,
+            expected_counter_offer,
+            min_collateral_out,
+            referrer,
+        } => {
+            let referrer = referrer
+                .map(|referrer| validate_addr(deps.api, &referrer))
+                .transpose()?;
+            let params = ExerciseParams {
+                max_extra,
+                deadline,
+                expected_counter_offer,
+                min_collateral_out,
+                referrer,
+                ..Default::default()
+            };
+            execute_execute(deps, env, info, params)
+        }
+        ExecuteMsg::ExecuteTo { recipient } => execute_execute_to(deps, env, info, recipient),
+        ExecuteMsg::ExecuteFor { on_behalf } => execute_execute_for(deps, env, info, on_behalf),
         ExecuteMsg::Burn {} => execute_burn(deps, env, info),
+        ExecuteMsg::Renounce {} => execute_renounce(deps, env, info),
+        ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
+        ExecuteMsg::Approve { spender } => execute_approve(deps, env, info, spender),
+        ExecuteMsg::CancelApproval {} => execute_cancel_approval(deps, env, info),
+        ExecuteMsg::ClaimRelease {} => execute_claim_release(deps, env, info),
+        ExecuteMsg::Sweep { denom } => execute_sweep(deps, env, info, denom),
+        ExecuteMsg::ForceExpire {} => execute_force_expire(deps, env, info),
+        ExecuteMsg::ProposeRescind {} => execute_propose_rescind(deps, env, info),
+        ExecuteMsg::AcceptRescind {} => execute_accept_rescind(deps, env, info),
+        ExecuteMsg::UpdateCounterOffer { counter_offer } => {
+            execute_update_counter_offer(deps, env, info, counter_offer)
+        }
+        ExecuteMsg::DepositCounterOffer {} => execute_deposit_counter_offer(deps, env, info),
+        ExecuteMsg::ClaimExpired {} => execute_claim_expired(deps, env, info),
+        ExecuteMsg::ExtendExpiry { new_expires } => {
+            execute_extend_expiry(deps, env, info, new_expires)
+        }
+        ExecuteMsg::ProposeAdmin { new_admin } => execute_propose_admin(deps, env, info, new_admin),
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, env, info),
+        ExecuteMsg::Withdraw {} => execute_withdraw(deps, env, info),
+        ExecuteMsg::PauseOption { paused } => execute_pause_option(deps, env, info, paused),
     }
 }
 
@@ -69,6 +483,9 @@ pub fn execute(
 ExecuteMsg::Transfer associated function - accepts 4 params, of which recipient is an extra one
 It firstly validates if sender of the message is the state.owner (instantiated one), if not - raises error
 Then it valides if recipient value is an appropriate address and sets it as a new state.owner to storage
+The spender approved through ExecuteMsg::Approve may transfer as well, any approval is cleared afterwards.
+A co-owned option cannot be transferred, a counter offer escrowed by the previous owner is returned to them.
+Transferring to the current owner changes nothing and writes nothing (action transfer_noop)
 */
 pub fn execute_transfer(
     deps: DepsMut,
@@ -76,18 +493,40 @@ pub fn execute_transfer(
     info: MessageInfo,
     recipient: String,
 ) -> Result<Response, ContractError> {
-    // ensure msg sender is the owner
-    let mut state = CONFIG.load(deps.storage)?;
-    if info.sender != state.owner {
-        return Err(ContractError::Unauthorized {});
+    // ensure msg sender is the owner or the spender approved by the owner
+    let mut state = load_option(deps.storage)?;
+    if info.sender != state.owner && state.approved.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            action: "transfer".to_string(),
+        });
+    }
+    if !state.owners.is_empty() {
+        return Err(ContractError::CoOwned {});
     }
 
-    // set new owner on state
-    state.owner = deps.api.addr_validate(&recipient)?;
-    CONFIG.save(deps.storage, &state)?;
+    // set new owner on state, restricted to the whitelist if one was configured
+    let recipient_addr = validate_addr(deps.api, &recipient)?;
+    if recipient_addr == state.owner {
+        return Ok(Response::new()
+            .add_attributes([("action", "transfer_noop"), ("owner", recipient.as_str())]));
+    }
+    if let Some(whitelist) = &state.transfer_whitelist {
+        if !whitelist.contains(&recipient_addr) {
+            return Err(ContractError::RecipientNotWhitelisted { recipient });
+        }
+    }
+    let previous_owner = std::mem::replace(&mut state.owner, recipient_addr);
+    // an approval is granted by an owner, so it never survives a change of owner
+    state.approved = None;
+    state.renounced = false;
+    let escrow = std::mem::take(&mut state.escrow);
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
 
-    let res =
-        Response::new().add_attributes([("action", "transfer"), ("owner", recipient.as_str())]);
+    let res = Response::new()
+        .add_messages(bank_send(&previous_owner, escrow))
+        .add_attributes([("action", "transfer"), ("owner", recipient.as_str())]);
     Ok(res)
 }
 
@@ -97,297 +536,6688 @@ ExecuteMsg::Execute associated function:
 - valides if the sender of the message is equal to the owner of the state, which is either the sender
 of InstantiateMsg or the owner transferred through ExecuteMsg::Transfer
 - checks if the option has not expired yet
-- checks if the funds sent through the message are equal to state.counter_offer attribute
+- checks if the funds sent through the message cover state.counter_offer attribute - each denom paid at least
+in full, denoms outside of it are refunded
 - if above conditions are met, the counter offer is sent to the creator of the option (InstantiateMsg)
 - collateral is sent to the owner of the state (which is either the sender of InstantiateMsg 
     or the owner transferred through ExecuteMsg::Transfer)
+- anything paid on top of the counter offer (overpaid or unrelated denoms) is refunded to the sender, with max_extra given it may be
+at most max_extra per denom (tolerating a counter offer raised between signing and execution)
+- with a deadline given, the tx fails once the block height is past it (e.g. stuck in the mempool)
+- with expected_counter_offer given, the tx fails if the stored counter_offer differs from it (e.g. the creator
+updated it in front of the execution)
+- with min_collateral_out given, the tx fails if the collateral holds less than it in any denom
+- a counter offer escrowed through DepositCounterOffer pays together with the attached funds
+- with a referrer given, the validated referrer receives the referral_bps share of the counter offer, the sender
+and the owner cannot refer themselves
+- past expiry with auto_burn_on_expired set, the option is burned instead and the attached funds refunded
+- with settlement_delay_blocks set (physical only, never with an NFT), the collateral is held until the recipient claims it (ClaimRelease)
+- a configured CW20 part of the counter offer is pulled from the sender's allowance to the creator
+- a configured exercise_hook is notified last, its failure failing the execution
 - removes the option from the storage
 */
 pub fn execute_execute(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    params: ExerciseParams,
 ) -> Result<Response, ContractError> {
-    // ensure msg sender is the owner
-    let state = CONFIG.load(deps.storage)?;
-    if info.sender != state.owner {
-        return Err(ContractError::Unauthorized {});
+    exercise(deps, env, info, params)
+}
+
+/*
+ExecuteMsg::ExecuteTo associated function - same as ExecuteMsg::Execute, but the collateral is delivered
+to the validated recipient instead of the owner (e.g. a custodian or a cold wallet).
+The counter offer still goes to the creator
+*/
+pub fn execute_execute_to(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let recipient = validate_addr(deps.api, &recipient)?;
+    let params = ExerciseParams {
+        collateral_recipient: Some(recipient),
+        ..Default::default()
+    };
+    exercise(deps, env, info, params)
+}
+
+/*
+ExecuteMsg::ExecuteFor associated function - delegated exercise: with on_behalf set, the spender approved
+through ExecuteMsg::Approve may execute for the owner (as may the owner themselves), paying the counter offer.
+The collateral still goes to the owner and the counter offer to the creator
+*/
+pub fn execute_execute_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    on_behalf: bool,
+) -> Result<Response, ContractError> {
+    let params = ExerciseParams {
+        delegated: on_behalf,
+        ..Default::default()
+    };
+    exercise(deps, env, info, params)
+}
+
+/// How an option is exercised, beyond the sender and the funds paid
+#[derive(Default)]
+pub struct ExerciseParams {
+    /// Collateral goes here instead of the owner
+    pub collateral_recipient: Option<Addr>,
+    /// Most that may be paid on top of the counter offer, per denom
+    pub max_extra: Option<Vec<Coin>>,
+    /// Sender exercises as the approved spender on behalf of the owner
+    pub delegated: bool,
+    /// Last block height the sender is willing to exercise at
+    pub deadline: Option<u64>,
+    /// Counter offer terms the sender agreed to, in any order
+    pub expected_counter_offer: Option<Vec<Coin>>,
+    /// Least collateral the sender is willing to receive, per denom
+    pub min_collateral_out: Option<Vec<Coin>>,
+    /// Receives the referral_bps share of the counter offer
+    pub referrer: Option<Addr>,
+}
+
+// shared by Execute, ExecuteTo and ExecuteFor
+fn exercise(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    params: ExerciseParams,
+) -> Result<Response, ContractError> {
+    // an exercise_hook called by a previous exercise must not exercise again before its reply
+    if EXECUTING
+        .may_load(deps.storage)
+        .map_err(ContractError::storage_load)?
+        .unwrap_or_default()
+    {
+        return Err(ContractError::Reentrancy {});
     }
 
-    // ensure not expired
-    if env.block.height >= state.expires {
-        return Err(ContractError::OptionExpired {
-            expired: state.expires,
-        });
+    // a stale broadcast fails instead of exercising at an unexpected height
+    if let Some(deadline) = params.deadline {
+        if env.block.height > deadline {
+            return Err(ContractError::DeadlineExceeded { deadline });
+        }
     }
 
-    // ensure sending proper counter_offer
-    if info.funds != state.counter_offer {
-        return Err(ContractError::CounterOfferMismatch {
-            offer: info.funds,
-            counter_offer: state.counter_offer,
-        });
+    let state = load_option(deps.storage)?;
+
+    // when so configured, executing an expired option cleans it up instead of failing
+    if state.auto_burn_on_expired && is_expired(&env, &state) {
+        return auto_burn(deps, env, info, state);
     }
 
-    // release counter_offer to creator
-    let mut res = Response::new();
-    res = res.add_message(BankMsg::Send {
-        to_address: state.creator.to_string(),
-        amount: state.counter_offer,
-    });
+    let collateral_recipient = params
+        .collateral_recipient
+        .clone()
+        .unwrap_or_else(|| state.owner.clone());
 
-    // release collateral to sender
-    res = res.add_message(BankMsg::Send {
-        to_address: state.owner.to_string(),
-        amount: state.collateral,
-    });
+    // catch corrupted state early rather than emitting bank messages doomed to fail
+    assert_valid_stored(deps.api, &state.owner)?;
+    assert_valid_stored(deps.api, &state.creator)?;
 
-    // delete the option
-    CONFIG.remove(deps.storage);
+    // neither party may be blocked by the compliance contract
+    assert_not_blocked(deps.as_ref(), &state)?;
 
-    res = res.add_attribute("action", "execute");
-    Ok(res)
-}
+    // execute only on the terms the sender has seen
+    if let Some(expected) = &params.expected_counter_offer {
+        if !coins_equal_unordered(expected, &state.counter_offer) {
+            return Err(ContractError::StateChanged {
+                counter_offer: state.counter_offer,
+            });
+        }
+    }
+    if let Some(min) = &params.min_collateral_out {
+        assert_collateral_out(&state.collateral, min)?;
+    }
 
-/* ExecuteMsg::Burn associated function:
-- checks if option has expired
-- checks if there are no funds sents
-- collateral is sent to the creator of the state
-- removes option from the storage
-*/
-pub fn execute_burn(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
-    // ensure is expired
-    let state = CONFIG.load(deps.storage)?;
-    if env.block.height < state.expires {
-        return Err(ContractError::OptionNotExpired {
-            expires: state.expires,
-        });
+    // cash settled options pay out the price difference instead of swapping
+    if let Settlement::CashSettled { oracle, strike } = state.settlement.clone() {
+        check_exercise(&state, &env, &info.sender, params.delegated)?;
+        return execute_cash_settled(deps, env, info, state, collateral_recipient, oracle, strike);
     }
 
-    // ensure sending proper counter_offer
-    if !info.funds.is_empty() {
-        return Err(ContractError::FundsSentWithBurn {});
+    // a counter_offer in a renamed denom may be paid under either name
+    let state = with_denom_aliases(deps.storage, state, &info.funds)
+        .map_err(ContractError::storage_load)?;
+    let funds = info.funds.clone();
+
+    // validate and build the bank messages, the same way SimulateExecute does
+    let msgs = compute_execute(&state, &env, &info.sender, &funds, &params)?;
+
+    // ensure the contract actually holds the collateral it is about to release
+    assert_contract_holds(deps.as_ref(), &env, &state.collateral)?;
+
+    // machine readable summary of what is sent, so indexers don't have to parse events
+    let data = ExecuteResult {
+        counter_offer: due_counter_offer(&state, &env, &with_escrow(&state, &funds))?,
+        collateral: state.collateral.clone(),
+        collateral_recipient: collateral_recipient.to_string(),
+    };
+    let hook = exercise_hook(&state, data.counter_offer.clone())?;
+
+    let mut res = Response::new()
+        .set_data(to_binary(&data)?)
+        .add_messages(msgs);
+
+    // the CW20 part of the counter_offer is pulled from the sender's allowance
+    if let Some(transfer) = cw20_transfer(&state, &info.sender)? {
+        res = res.add_submessage(transfer);
     }
 
-    // release collateral to creator
-    let mut res = Response::new();
-    res = res.add_message(BankMsg::Send {
-        to_address: state.creator.to_string(),
-        amount: state.collateral,
-    });
+    // NFT collateral is transferred after all bank messages
+    if let Some(msg) = release_nft(&state, &collateral_recipient)? {
+        res = res.add_message(msg);
+    }
 
-    // delete the option
-    CONFIG.remove(deps.storage);
+    // the hook is notified once everything has been paid out, locking execution until its reply
+    if let Some(hook) = hook {
+        EXECUTING
+            .save(deps.storage, &true)
+            .map_err(ContractError::storage_save)?;
+        res = res.add_submessage(hook);
+    }
 
-    res = res.add_attribute("action", "burn");
-    Ok(res)
-}
+    // bound the gas an exercise may burn through its messages
+    assert_max_submsgs(&state, &res)?;
 
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+    // a delayed settlement keeps the coin collateral until the recipient claims it
+    if state.settlement_delay_blocks > 0 && !state.collateral.is_empty() {
+        let release_height = env.block.height + state.settlement_delay_blocks;
+        let release = PendingRelease {
+            recipient: collateral_recipient.clone(),
+            amount: state.collateral.clone(),
+            release_height,
+        };
+        PENDING_RELEASE
+            .save(deps.storage, &release)
+            .map_err(ContractError::storage_save)?;
+        res = res.add_attribute("release_height", release_height.to_string());
     }
+
+    // keep a receipt, then delete the option
+    save_receipt(deps.storage, &data, &info.sender, &env)?;
+    CONFIG.remove(deps.storage);
+
+    res = res
+        .add_attribute("action", "execute")
+        .add_attributes(exercise_attributes(&data, &env));
+    Ok(res)
 }
 
-fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
-    let state = CONFIG.load(deps.storage)?;
-    Ok(state)
+/*
+analytics attributes shared by every way of exercising, so volume and realized spreads can be read from events:
+the collateral released and the counter_offer paid as coin strings, and the height of exercise
+*/
+fn exercise_attributes(data: &ExecuteResult, env: &Env) -> Vec<(&'static str, String)> {
+    vec![
+        ("collateral_value", coins_to_string(&data.collateral)),
+        ("counter_offer_value", coins_to_string(&data.counter_offer)),
+        ("exercised_at_height", env.block.height.to_string()),
+    ]
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{attr, coins, CosmosMsg, coin};
+/*
+checks shared by every way of exercising: the sender must be the owner or a co-owner (or, delegated, the
+approved spender) and not the creator unless allow_creator_exercise,
+the option must not be expired and an expected NFT collateral must have been posted
+*/
+fn check_exercise(
+    state: &State,
+    env: &Env,
+    sender: &Addr,
+    delegated: bool,
+) -> Result<(), ContractError> {
+    // the creator holding their own option may be barred from exercising it
+    if !state.allow_creator_exercise && *sender == state.creator {
+        return Err(ContractError::CreatorCannotExercise {});
+    }
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies();
+    // ensure msg sender is the owner or exercises on the owner's behalf
+    let approved = delegated && state.approved.as_ref() == Some(sender);
+    let co_owner = state.owners.iter().any(|(owner, _)| owner == sender);
+    if *sender != state.owner && !approved && !co_owner {
+        return Err(ContractError::Unauthorized {
+            action: "execute".to_string(),
+        });
+    }
 
-        let msg = InstantiateMsg {
-            counter_offer: coins(40, "ETH"),
-            expires: 100_000,
-        };
-        let info = mock_info("creator", &coins(1, "BTC"));
+    // ensure not expired
+    assert_exclusive(env, state, Phase::Exercisable)?;
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+    // a paused option cannot be exercised
+    if state.paused {
+        return Err(ContractError::OptionPaused {});
+    }
 
-        // it worked, let's query the state
-        let res = query_config(deps.as_ref()).unwrap();
-        assert_eq!(100_000, res.expires);
-        assert_eq!("creator", res.owner.as_str());
-        assert_eq!("creator", res.creator.as_str());
-        assert_eq!(coins(1, "BTC"), res.collateral);
-        assert_eq!(coins(40, "ETH"), res.counter_offer);
+    // an option expecting an NFT cannot be executed before the NFT was posted
+    if state.nft_contract.is_some() && state.nft_collateral.is_none() {
+        return Err(ContractError::NftNotReceived {});
     }
+    Ok(())
+}
 
-    #[test]
-    fn transfer() {
-        let mut deps = mock_dependencies();
+/*
+pure part of ExecuteMsg::Execute for physically settled options, also used to simulate execution:
+- runs the exercise checks and ensures funds cover the counter_offer (in any order, each denom paid at
+least in full)
+- returns the messages in their guaranteed order, relied upon downstream:
+[0] counter_offer to creator (or, with receive_as set, to the router swapping it into that CW20 for the creator,
+or with payout_splits set, one send per payee taking [0] up to [payees - 1] and shifting the rest; skipped
+when there is nothing to pay, as for an empty counter_offer),
+[1] collateral to recipient (skipped when it holds no coins or settlement_delay_blocks holds it back,
+or with co-owners, one send per co-owner by their bps shifting the rest),
+then the refund of anything paid on top of the counter_offer to sender (only on overpayment, capped by
+max_extra per denom when given), up to what the sender attached,
+then the rest of that refund, paid out of the escrow, to the owner,
+then the holder_rebate_bps share of the counter_offer back to sender (only with a rebate, rounded down in
+favour of the creator, whose payment at [0] is reduced by it),
+then the referral_bps share of the counter_offer to the referrer (only with a referrer, rounded down and
+likewise reducing [0]).
+Any new message must be appended after these. With split_sends set, each send is split per denom in place
+*/
+pub fn compute_execute(
+    state: &State,
+    env: &Env,
+    sender: &Addr,
+    funds: &[Coin],
+    params: &ExerciseParams,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    check_exercise(state, env, sender, params.delegated)?;
+    let collateral_recipient = params.collateral_recipient.as_ref().unwrap_or(&state.owner);
 
-        let msg = InstantiateMsg {
-            counter_offer: coins(40, "ETH"),
-            expires: 100_000,
-        };
-        let info = mock_info("creator", &coins(1, "BTC"));
+    // an escrowed counter_offer pays together with anything attached
+    let attached = funds;
+    let funds = &with_escrow(state, attached);
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+    // a referral fee paid back to the exercising side would only be a discount on the counter_offer
+    if let Some(referrer) = &params.referrer {
+        if referrer == sender
+            || *referrer == state.owner
+            || state.owners.iter().any(|(owner, _)| owner == referrer)
+        {
+            return Err(ContractError::SelfReferral {
+                referrer: referrer.to_string(),
+            });
+        }
+    }
 
-        // random cannot transfer
-        let info = mock_info("anyone", &[]);
-        let err =
-            execute_transfer(deps.as_mut(), mock_env(), info, "anyone".to_string()).unwrap_err();
-        match err {
-            ContractError::Unauthorized {} => {}
-            e => panic!("unexpected error: {}", e),
+    // nothing sent towards a counter_offer that is due
+    if funds.is_empty() && !state.counter_offer.is_empty() {
+        return Err(ContractError::NoEscrow {});
+    }
+
+    // a decaying counter_offer is due at its current, discounted amount, possibly converted into one denom
+    let counter_offer = due_counter_offer(state, env, funds)?;
+    let excess = counter_offer_excess(funds, &counter_offer).ok_or_else(|| {
+        // name the denoms paid instead of the asked ones, when that is what went wrong
+        let denoms = unexpected_denoms(funds, &counter_offer);
+        if denoms.is_empty() {
+            ContractError::CounterOfferMismatch {
+                offer: funds.to_vec(),
+                counter_offer: counter_offer.clone(),
+            }
+        } else {
+            ContractError::UnexpectedDenoms { denoms }
         }
+    })?;
+    if let Some(max_extra) = &params.max_extra {
+        assert_within_tolerance(&excess, max_extra)?;
+    }
 
-        // owner can transfer
-        let info = mock_info("creator", &[]);
-        let res = execute_transfer(deps.as_mut(), mock_env(), info, "someone".to_string()).unwrap();
-        assert_eq!(res.attributes.len(), 2);
-        assert_eq!(res.attributes[0], attr("action", "transfer"));
+    // release counter_offer to creator, less the holder rebate and the referral fee
+    let rebate = bps_share(&counter_offer, state.holder_rebate_bps)?;
+    let referral = match &params.referrer {
+        Some(_) => bps_share(&counter_offer, state.referral_bps)?,
+        None => vec![],
+    };
+    let creator_share = subtract_coins(&counter_offer, &rebate)
+        .and_then(|rest| subtract_coins(&rest, &referral))
+        .ok_or(ContractError::Overflow {})?;
+    let mut msgs: Vec<CosmosMsg> = if state.payout_splits.is_empty() {
+        pay_creator(state, creator_share)?.into_iter().collect()
+    } else {
+        split_payout(&state.payout_splits, &creator_share)?
+            .into_iter()
+            .filter_map(|(payee, amount)| bank_send(&payee, amount))
+            .map(CosmosMsg::from)
+            .collect()
+    };
 
-        // check updated properly
-        let res = query_config(deps.as_ref()).unwrap();
-        assert_eq!("someone", res.owner.as_str());
-        assert_eq!("creator", res.creator.as_str());
+    // release collateral to sender (or the recipient it is directed to), unless it is held back for a while
+    if state.settlement_delay_blocks == 0 {
+        if state.owners.is_empty() {
+            msgs.extend(collateral_bank_msg(state, collateral_recipient).map(CosmosMsg::from));
+        } else {
+            // co-owners split the collateral, which none of them may redirect
+            if params.collateral_recipient.is_some() {
+                return Err(ContractError::CoOwned {});
+            }
+            msgs.extend(
+                split_payout(&state.owners, &state.collateral)?
+                    .into_iter()
+                    .filter_map(|(owner, amount)| bank_send(&owner, amount))
+                    .map(CosmosMsg::from),
+            );
+        }
     }
 
-    #[test]
-    fn execute() {
-        let mut deps = mock_dependencies();
+    // refund overpayment to sender, as far as it was attached, the rest came out of the owner's escrow
+    let (to_sender, to_owner) = if *sender == state.owner {
+        (excess, vec![])
+    } else {
+        split_excess(&excess, attached)
+    };
+    msgs.extend(bank_send(sender, to_sender).map(CosmosMsg::from));
+    msgs.extend(bank_send(&state.owner, to_owner).map(CosmosMsg::from));
+
+    // rebate part of the counter_offer to sender
+    msgs.extend(bank_send(sender, rebate).map(CosmosMsg::from));
+
+    // referral fee to whoever brought the sender in
+    if let Some(referrer) = &params.referrer {
+        msgs.extend(bank_send(referrer, referral).map(CosmosMsg::from));
+    }
+    Ok(split_sends(state, msgs))
+}
+
+/*
+each payee's bps share of every coin (of the counter_offer, or the collateral for co-owners), rounded down -
+the dust left by rounding goes to the first payee, so the whole amount is paid out
+*/
+fn split_payout(
+    splits: &[(Addr, u16)],
+    amount: &[Coin],
+) -> Result<Vec<(Addr, Vec<Coin>)>, ContractError> {
+    let mut payouts: Vec<(Addr, Vec<Coin>)> = splits
+        .iter()
+        .map(|(payee, _)| (payee.clone(), vec![]))
+        .collect();
+    for coin in amount {
+        let shares = splits
+            .iter()
+            .map(|(_, bps)| mul_bps(coin.amount, *bps))
+            .collect::<Result<Vec<_>, _>>()?;
+        // bps sum to 10000 (validated at instantiate), so the shares never exceed the coin
+        let dust = coin.amount
+            - shares
+                .iter()
+                .fold(Uint128::zero(), |sum, share| sum + share);
+        for (i, ((_, amount), share)) in payouts.iter_mut().zip(shares).enumerate() {
+            amount.push(Coin {
+                denom: coin.denom.clone(),
+                amount: if i == 0 { share + dust } else { share },
+            });
+        }
+    }
+    Ok(payouts)
+}
+
+/*
+counter_offer with every aliased denom renamed to its other name when funds pay it under that name only, so a
+payment in either the old or the new denom matches. The funds themselves are never renamed: the coins forwarded
+and refunded are the ones actually received
+*/
+fn match_denom_aliases(
+    counter_offer: Vec<Coin>,
+    funds: &[Coin],
+    aliases: &[(String, String)],
+) -> Vec<Coin> {
+    if aliases.is_empty() {
+        return counter_offer;
+    }
+    let paid = aggregate_coins(funds);
+    let renamed = counter_offer
+        .into_iter()
+        .map(|coin| {
+            let other = aliases
+                .iter()
+                .filter_map(|(old, new)| {
+                    if *old == coin.denom {
+                        Some(new)
+                    } else if *new == coin.denom {
+                        Some(old)
+                    } else {
+                        None
+                    }
+                })
+                .find(|other| {
+                    !paid.contains_key(coin.denom.as_str()) && paid.contains_key(other.as_str())
+                });
+            match other {
+                Some(denom) => Coin {
+                    denom: denom.clone(),
+                    amount: coin.amount,
+                },
+                None => coin,
+            }
+        })
+        .collect();
+    normalize_coins(renamed)
+}
+
+/*
+state of an exercise (executed, simulated or quoted) with its counter_offer matched through the configured denom
+aliases against the funds paid, together with anything escrowed
+*/
+fn with_denom_aliases(storage: &dyn Storage, mut state: State, funds: &[Coin]) -> StdResult<State> {
+    let aliases = DENOM_ALIASES.may_load(storage)?.unwrap_or_default();
+    let paid = with_escrow(&state, funds);
+    state.counter_offer = match_denom_aliases(state.counter_offer, &paid, &aliases);
+    Ok(state)
+}
+
+/*
+splits an overpayment into what goes back to the sender - at most what they attached, per denom - and what goes
+back to the owner, having come out of their escrow (which differ when the approved spender executes for the owner)
+*/
+fn split_excess(excess: &[Coin], attached: &[Coin]) -> (Vec<Coin>, Vec<Coin>) {
+    let attached = aggregate_coins(attached);
+    excess
+        .iter()
+        .map(|coin| {
+            let back = coin.amount.min(
+                attached
+                    .get(coin.denom.as_str())
+                    .copied()
+                    .unwrap_or_default(),
+            );
+            (
+                Coin {
+                    denom: coin.denom.clone(),
+                    amount: back,
+                },
+                Coin {
+                    denom: coin.denom.clone(),
+                    amount: coin.amount - back,
+                },
+            )
+        })
+        .unzip()
+}
+
+/*
+funds paying for an exercise - those attached plus whatever the owner escrowed through DepositCounterOffer
+*/
+fn with_escrow(state: &State, funds: &[Coin]) -> Vec<Coin> {
+    if state.escrow.is_empty() {
+        return funds.to_vec();
+    }
+    normalize_coins([funds, &state.escrow].concat())
+}
+
+/*
+every bank send goes through here - zero coins (e.g. a fee or rebate rounded down to nothing) are dropped,
+as some chains reject them, and no message at all is sent when nothing is left
+*/
+fn bank_send(to_address: &Addr, amount: Vec<Coin>) -> Option<BankMsg> {
+    let amount = filter_nonzero(amount);
+    if amount.is_empty() {
+        return None;
+    }
+    Some(BankMsg::Send {
+        to_address: to_address.to_string(),
+        amount,
+    })
+}
+
+pub fn filter_nonzero(coins: Vec<Coin>) -> Vec<Coin> {
+    coins.into_iter().filter(|c| !c.amount.is_zero()).collect()
+}
+
+// bps of every counter_offer coin (holder rebate, referral fee), rounded down and dropping coins that round to zero
+fn bps_share(counter_offer: &[Coin], bps: u16) -> Result<Vec<Coin>, ContractError> {
+    let mut rebate = vec![];
+    for coin in counter_offer {
+        let amount = mul_bps(coin.amount, bps)?;
+        if !amount.is_zero() {
+            rebate.push(Coin {
+                denom: coin.denom.clone(),
+                amount,
+            });
+        }
+    }
+    Ok(rebate)
+}
+
+/*
+with split_sends set, every multi-coin BankMsg::Send becomes one send per denom, in denom order, for chains
+that dislike multi-coin sends. The order of the original messages is kept, only their indexes shift
+*/
+fn split_sends(state: &State, msgs: Vec<CosmosMsg>) -> Vec<CosmosMsg> {
+    if !state.split_sends {
+        return msgs;
+    }
+    msgs.into_iter()
+        .flat_map(|msg| match msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => aggregate_coins(&amount)
+                .into_iter()
+                .map(|(denom, amount)| {
+                    BankMsg::Send {
+                        to_address: to_address.clone(),
+                        amount: coins(amount.u128(), denom),
+                    }
+                    .into()
+                })
+                .collect(),
+            msg => vec![msg],
+        })
+        .collect()
+}
+
+/*
+counter_offer payment to the creator - sent as is, or through the router swapping it into the receive_as CW20,
+which the router delivers to the creator directly. Nothing is sent when no coins are left to pay
+*/
+fn pay_creator(
+    state: &State,
+    counter_offer: Vec<Coin>,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    let receive_as = match &state.receive_as {
+        Some(receive_as) => receive_as,
+        None => return Ok(bank_send(&state.creator, counter_offer).map(CosmosMsg::from)),
+    };
+    let counter_offer = filter_nonzero(counter_offer);
+    if counter_offer.is_empty() {
+        return Ok(None);
+    }
+    let router = state
+        .router
+        .as_ref()
+        .ok_or(ContractError::MissingRouter {})?;
+    Ok(Some(
+        WasmMsg::Execute {
+            contract_addr: router.to_string(),
+            msg: to_binary(&RouterExecuteMsg::Swap {
+                ask_token: receive_as.to_string(),
+                recipient: state.creator.to_string(),
+            })?,
+            funds: counter_offer,
+        }
+        .into(),
+    ))
+}
+
+/*
+cash settled flavour of ExecuteMsg::Execute, entered after the exercise checks:
+- the current price is queried from the oracle contract
+- the collateral recipient (the owner by default) is paid out as computed by compute_cash_settled
+- removes the option from the storage
+*/
+fn execute_cash_settled(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    state: State,
+    collateral_recipient: Addr,
+    oracle: Addr,
+    strike: Uint128,
+) -> Result<Response, ContractError> {
+    let price = query_price(deps.as_ref(), &oracle)?;
+    let (payout, msgs) =
+        compute_cash_settled(&state, &info.funds, &collateral_recipient, price, strike)?;
+
+    // ensure the contract actually holds the collateral it is about to release
+    assert_contract_holds(deps.as_ref(), &env, &state.collateral)?;
+
+    let data = ExecuteResult {
+        counter_offer: vec![],
+        collateral: vec![payout],
+        collateral_recipient: collateral_recipient.to_string(),
+    };
+    let mut res = Response::new()
+        .set_data(to_binary(&data)?)
+        .add_messages(msgs)
+        .add_attributes([("action", "execute"), ("settlement", "cash")])
+        .add_attribute("price", price.to_string())
+        .add_attributes(exercise_attributes(&data, &env));
+
+    // the hook is notified once everything has been paid out, no counter_offer is paid here
+    if let Some(hook) = exercise_hook(&state, vec![])? {
+        EXECUTING
+            .save(deps.storage, &true)
+            .map_err(ContractError::storage_save)?;
+        res = res.add_submessage(hook);
+    }
+    assert_max_submsgs(&state, &res)?;
+
+    // keep a receipt, then delete the option
+    save_receipt(deps.storage, &data, &info.sender, &env)?;
+    CONFIG.remove(deps.storage);
+
+    Ok(res)
+}
+
+/*
+receipt of the exercise, what was paid and what was received for it - queryable after the option is gone
+*/
+fn save_receipt(
+    storage: &mut dyn Storage,
+    data: &ExecuteResult,
+    exerciser: &Addr,
+    env: &Env,
+) -> Result<(), ContractError> {
+    let receipt = Receipt {
+        option_id: OPTION_ID,
+        exerciser: exerciser.clone(),
+        paid: data.counter_offer.clone(),
+        received: data.collateral.clone(),
+        height: env.block.height,
+    };
+    RECEIPTS
+        .save(storage, OPTION_ID, &receipt)
+        .map_err(ContractError::storage_save)
+}
+
+/*
+with max_submsgs set, an exercise emitting more messages than that (e.g. many payout splits and a hook)
+fails instead of burning unbounded gas
+*/
+fn assert_max_submsgs(state: &State, res: &Response) -> Result<(), ContractError> {
+    match state.max_submsgs {
+        Some(max) if res.messages.len() > max as usize => Err(ContractError::TooManyMessages {
+            count: res.messages.len(),
+            max,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/*
+notification of the exercise_hook contract, if configured. Sent as a submessage replying always, so reply can
+surface the hook failure and, on success, lift the EXECUTING lock held while the hook runs
+*/
+fn exercise_hook(state: &State, amount: Vec<Coin>) -> StdResult<Option<SubMsg>> {
+    let hook = match &state.exercise_hook {
+        Some(hook) => hook,
+        None => return Ok(None),
+    };
+    let msg = WasmMsg::Execute {
+        contract_addr: hook.to_string(),
+        msg: to_binary(&HookExecuteMsg::OptionExecuted {
+            creator: state.creator.to_string(),
+            owner: state.owner.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    };
+    Ok(Some(SubMsg::reply_always(msg, EXERCISE_HOOK_REPLY_ID)))
+}
+
+/*
+TransferFrom of the CW20 counter_offer from the sender to the creator, if configured. It fails without
+a sufficient allowance granted to this contract, reported through reply
+*/
+fn cw20_transfer(state: &State, sender: &Addr) -> StdResult<Option<SubMsg>> {
+    let (token, amount) = match &state.cw20_counter_offer {
+        Some(cw20) => cw20,
+        None => return Ok(None),
+    };
+    let msg = WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+            owner: sender.to_string(),
+            recipient: state.creator.to_string(),
+            amount: *amount,
+        })?,
+        funds: vec![],
+    };
+    Ok(Some(SubMsg::reply_on_error(msg, CW20_TRANSFER_REPLY_ID)))
+}
+
+/*
+reply entry point - the CW20 transfer replies on error only, the exercise_hook always. Either failure fails
+the exercise with an error saying which part failed (reverting the lock with it), a successful hook releases
+the EXECUTING lock
+*/
+#[entry_point]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match (msg.id, msg.result) {
+        (EXERCISE_HOOK_REPLY_ID, SubMsgResult::Err(error)) => {
+            Err(ContractError::HookFailed { error })
+        }
+        (CW20_TRANSFER_REPLY_ID, SubMsgResult::Err(error)) => {
+            Err(ContractError::Cw20TransferFailed { error })
+        }
+        (EXERCISE_HOOK_REPLY_ID, SubMsgResult::Ok(_)) => {
+            EXECUTING.remove(deps.storage);
+            Ok(Response::new())
+        }
+        (CW20_TRANSFER_REPLY_ID, SubMsgResult::Ok(_)) => Ok(Response::new()),
+        (id, _) => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+/*
+with a compliance contract configured, it is asked whether the creator or the owner is blocked
+before anything is paid out
+*/
+fn assert_not_blocked(deps: Deps, state: &State) -> Result<(), ContractError> {
+    let compliance = match &state.compliance_contract {
+        Some(compliance) => compliance,
+        None => return Ok(()),
+    };
+    for address in [&state.creator, &state.owner] {
+        let IsBlockedResponse { blocked } = deps.querier.query_wasm_smart(
+            compliance,
+            &ComplianceQueryMsg::IsBlocked {
+                address: address.to_string(),
+            },
+        )?;
+        if blocked {
+            return Err(ContractError::AddressBlocked {
+                address: address.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn query_price(deps: Deps, oracle: &Addr) -> StdResult<Uint128> {
+    let PriceResponse { price } = deps
+        .querier
+        .query_wasm_smart(oracle, &OracleQueryMsg::Price {})?;
+    Ok(price)
+}
+
+/*
+pure part of cash settlement:
+- no counter_offer is paid, so no funds may be sent
+- the option must be in the money (price above strike)
+- the oracle prices one unit of the collateral denom in the same quote units as the strike, so the holder is
+owed (price - strike) per unit of collateral, worth collateral * (price - strike) / price in the collateral
+itself (rounded down), which never exceeds the collateral
+- whatever is left of the collateral goes back to the creator
+returns the payout and the bank messages, payout first
+*/
+fn compute_cash_settled(
+    state: &State,
+    funds: &[Coin],
+    collateral_recipient: &Addr,
+    price: Uint128,
+    strike: Uint128,
+) -> Result<(Coin, Vec<BankMsg>), ContractError> {
+    if !funds.is_empty() {
+        return Err(ContractError::CounterOfferMismatch {
+            offer: funds.to_vec(),
+            counter_offer: vec![],
+        });
+    }
+    if price <= strike {
+        return Err(ContractError::OutOfTheMoney { price, strike });
+    }
+
+    // collateral is a single coin, validated at instantiate
+    let collateral = &state.collateral[0];
+    let payout = collateral
+        .amount
+        .checked_multiply_ratio(price - strike, price)
+        .map_err(|_| ContractError::Overflow {})?;
+    let remainder = collateral.amount - payout;
+    let payout = Coin {
+        denom: collateral.denom.clone(),
+        amount: payout,
+    };
+
+    // pay the price difference to the owner
+    let mut msgs: Vec<BankMsg> = bank_send(collateral_recipient, vec![payout.clone()])
+        .into_iter()
+        .collect();
+
+    // return the rest of the collateral to creator
+    msgs.extend(bank_send(
+        &state.creator,
+        coins(remainder.u128(), &collateral.denom),
+    ));
+    Ok((payout, msgs))
+}
+
+/* ExecuteMsg::Burn associated function:
+- checks if option has expired
+- in BurnMode::CreatorOnly checks if the sender is the creator, otherwise anyone may burn
+- checks if there are no funds sents
+- collateral is sent to the burn beneficiary - the creator of the state unless configured to be the owner (split
+between co-owners by their bps),
+except for the burn_reward paid to the sender (the keeper)
+- escrow not claimed by the owner yet is returned to them
+- removes option from the storage
+*/
+pub fn execute_burn(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    // ensure is expired
+    let state = load_option(deps.storage)?;
+    assert_exclusive(&env, &state, Phase::Expired)?;
+
+    // ensure the sender may burn
+    if state.burn_mode == BurnMode::CreatorOnly && info.sender != state.creator {
+        return Err(ContractError::Unauthorized {
+            action: "burn".to_string(),
+        });
+    }
+
+    // ensure sending proper counter_offer
+    if !info.funds.is_empty() {
+        return Err(ContractError::FundsSentWithBurn {});
+    }
+
+    let res = burn(deps, &env, state, &info.sender, vec![])?;
+    Ok(res.add_attribute("action", "burn"))
+}
+
+/*
+execute attempt on an expired option with auto_burn_on_expired set - burned the same way as ExecuteMsg::Burn
+(burn_mode still applies), with the funds attached for the counter offer refunded to the sender
+*/
+fn auto_burn(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    state: State,
+) -> Result<Response, ContractError> {
+    if state.burn_mode == BurnMode::CreatorOnly && info.sender != state.creator {
+        return Err(ContractError::Unauthorized {
+            action: "burn".to_string(),
+        });
+    }
+
+    let res = burn(deps, &env, state, &info.sender, info.funds)?;
+    Ok(res.add_attribute("action", "auto_burn"))
+}
+
+/*
+releases the collateral of an expired option and removes it, shared by Burn and auto_burn.
+keeper receives the burn_reward, refund is sent back to keeper last. The caller adds the action attribute
+*/
+fn burn(
+    deps: DepsMut,
+    env: &Env,
+    state: State,
+    keeper: &Addr,
+    refund: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    // neither party may be blocked by the compliance contract
+    assert_not_blocked(deps.as_ref(), &state)?;
+
+    // ensure the contract actually holds the collateral it is about to release
+    assert_contract_holds(deps.as_ref(), env, &state.collateral)?;
+
+    // collateral reverts to the creator, or to the current holder if so configured
+    let beneficiary = match state.burn_beneficiary {
+        BurnBeneficiary::Creator => &state.creator,
+        BurnBeneficiary::Owner => &state.owner,
+    };
+
+    // the keeper reward is carved out of the collateral, covered as validated at instantiate
+    let remainder =
+        subtract_coins(&state.collateral, &state.burn_reward).ok_or(ContractError::Overflow {})?;
+
+    // machine readable summary of what is sent, nothing is paid on burn
+    let data = ExecuteResult {
+        counter_offer: vec![],
+        collateral: remainder.clone(),
+        collateral_recipient: beneficiary.to_string(),
+    };
+
+    // release collateral to beneficiary, co-owners splitting it by their bps
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    if matches!(state.burn_beneficiary, BurnBeneficiary::Owner) && !state.owners.is_empty() {
+        msgs.extend(
+            split_payout(&state.owners, &remainder)?
+                .into_iter()
+                .filter_map(|(owner, amount)| bank_send(&owner, amount))
+                .map(CosmosMsg::from),
+        );
+    } else {
+        msgs.extend(bank_send(beneficiary, remainder).map(CosmosMsg::from));
+    }
+    msgs.extend(release_nft(&state, beneficiary)?);
+
+    // reward the keeper cleaning up the expired option
+    msgs.extend(bank_send(keeper, state.burn_reward.clone()).map(CosmosMsg::from));
+
+    // return unclaimed escrow to owner
+    msgs.extend(bank_send(&state.owner, state.escrow.clone()).map(CosmosMsg::from));
+
+    // return anything attached to the attempt
+    msgs.extend(bank_send(keeper, refund).map(CosmosMsg::from));
+
+    let res = Response::new()
+        .set_data(to_binary(&data)?)
+        .add_messages(split_sends(&state, msgs));
+
+    // delete the option
+    CONFIG.remove(deps.storage);
+
+    Ok(res)
+}
+
+/* ExecuteMsg::DepositCounterOffer associated function:
+- only the owner may deposit, while the option is still exercisable
+- a cash settled option has no counter_offer to deposit
+- info.funds (non-empty, without zero coins) are added to the escrow, paying the counter_offer on a later Execute
+- the escrow goes back to the owner if the option expires unexercised (ClaimExpired, or Burn)
+*/
+pub fn execute_deposit_counter_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the owner
+    let mut state = load_option(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            action: "deposit_counter_offer".to_string(),
+        });
+    }
+
+    // ensure not expired
+    assert_exclusive(&env, &state, Phase::Exercisable)?;
+    if state.settlement != Settlement::Physical {
+        return Err(ContractError::NoCounterOffer {});
+    }
+
+    validate::non_empty(&info.funds)?;
+    validate::no_zero_amounts(&info.funds)?;
+    state.escrow = normalize_coins([state.escrow, info.funds].concat());
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new().add_attributes([
+        ("action", "deposit_counter_offer".to_string()),
+        ("escrow", coins_to_string(&state.escrow)),
+    ]);
+    Ok(res)
+}
+
+/* ExecuteMsg::ClaimExpired associated function:
+- only the owner may claim, and only once the option expired unexercised
+- the escrowed counter_offer is returned to the owner, the collateral stays put awaiting burn
+*/
+pub fn execute_claim_expired(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the owner
+    let mut state = load_option(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            action: "claim_expired".to_string(),
+        });
+    }
+
+    // ensure is expired
+    assert_exclusive(&env, &state, Phase::Expired)?;
+
+    if state.escrow.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    assert_contract_holds(deps.as_ref(), &env, &state.escrow)?;
+
+    let escrow = std::mem::take(&mut state.escrow);
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new()
+        .add_messages(bank_send(&state.owner, escrow))
+        .add_attributes([("action", "claim_expired"), ("owner", state.owner.as_str())]);
+    Ok(res)
+}
+
+/* ExecuteMsg::ExtendExpiry associated function:
+- only the owner may extend, while the option is still exercisable and only if an extension_fee is configured
+- info.funds must match the extension_fee exactly (in any order), it is forwarded to the creator
+- new_expires must be after the current expiry and within MAX_EXPIRY_BLOCKS from now
+*/
+pub fn execute_extend_expiry(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_expires: u64,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the owner
+    let mut state = load_option(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            action: "extend_expiry".to_string(),
+        });
+    }
+
+    // ensure not expired
+    assert_exclusive(&env, &state, Phase::Exercisable)?;
+
+    if state.extension_fee.is_empty() {
+        return Err(ContractError::ExtensionDisabled {});
+    }
+    if !coins_equal_unordered(&info.funds, &state.extension_fee) {
+        return Err(ContractError::ExtensionFeeMismatch {
+            paid: info.funds,
+            fee: state.extension_fee,
+        });
+    }
+
+    if new_expires <= state.expires {
+        return Err(ContractError::ExpiryNotIncreased {
+            expires: state.expires,
+        });
+    }
+    if new_expires.saturating_sub(env.block.height) > MAX_EXPIRY_BLOCKS {
+        return Err(ContractError::ExpiryTooFar {
+            max: MAX_EXPIRY_BLOCKS,
+        });
+    }
+
+    state.expires = new_expires;
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    // forward the fee to creator
+    let res = Response::new()
+        .add_messages(bank_send(&state.creator, state.extension_fee))
+        .add_attribute("action", "extend_expiry")
+        .add_attribute("expires", new_expires.to_string());
+    Ok(res)
+}
+
+/* ExecuteMsg::ReceiveNft associated function - the CW721 send hook:
+- info.sender is the NFT contract, which has to be the one configured at instantiate
+- the NFT has to be sent by the creator, and only one NFT can be posted
+- the contract and token id are stored as nft_collateral, released together with the coin collateral
+*/
+pub fn execute_receive_nft(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let mut state = load_option(deps.storage)?;
+    if state.nft_contract.as_ref() != Some(&info.sender) {
+        return Err(ContractError::UnexpectedNftContract {
+            contract: info.sender.to_string(),
+        });
+    }
+    if msg.sender != state.creator.as_str() {
+        return Err(ContractError::Unauthorized {
+            action: "receive_nft".to_string(),
+        });
+    }
+    if state.nft_collateral.is_some() {
+        return Err(ContractError::NftAlreadyReceived {});
+    }
+
+    state.nft_collateral = Some((info.sender, msg.token_id.clone()));
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new().add_attributes([
+        ("action", "receive_nft"),
+        ("token_id", msg.token_id.as_str()),
+    ]);
+    Ok(res)
+}
+
+/* ExecuteMsg::Approve associated function:
+- only the owner may approve
+- the validated spender is stored in state.approved and can then transfer the option on the owner's behalf
+- a new approval replaces the previous one
+*/
+pub fn execute_approve(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    spender: String,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the owner
+    let mut state = load_option(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            action: "approve".to_string(),
+        });
+    }
+
+    state.approved = Some(validate_addr(deps.api, &spender)?);
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    let res =
+        Response::new().add_attributes([("action", "approve"), ("spender", spender.as_str())]);
+    Ok(res)
+}
+
+/* ExecuteMsg::CancelApproval associated function:
+- only the owner may cancel, and only an existing approval
+- state.approved is cleared, so the spender can no longer transfer the option
+*/
+pub fn execute_cancel_approval(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the owner
+    let mut state = load_option(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            action: "cancel_approval".to_string(),
+        });
+    }
+
+    let spender = state.approved.take().ok_or(ContractError::NoApproval {})?;
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new()
+        .add_attributes([("action", "cancel_approval"), ("spender", spender.as_str())]);
+    Ok(res)
+}
+
+/* ExecuteMsg::ClaimRelease associated function:
+- a release must be pending, recorded by an execution with settlement_delay_blocks set
+- only its recipient may claim, once the release height is reached
+- the held collateral is sent and the pending release removed
+*/
+pub fn execute_claim_release(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let release = PENDING_RELEASE
+        .may_load(deps.storage)
+        .map_err(ContractError::storage_load)?
+        .ok_or(ContractError::NoPendingRelease {})?;
+
+    // ensure msg sender is the recipient
+    if info.sender != release.recipient {
+        return Err(ContractError::Unauthorized {
+            action: "claim_release".to_string(),
+        });
+    }
+
+    // ensure the hold is over
+    if env.block.height < release.release_height {
+        return Err(ContractError::ReleaseNotReady {
+            release_height: release.release_height,
+        });
+    }
+
+    PENDING_RELEASE.remove(deps.storage);
+
+    let res = Response::new()
+        .add_messages(bank_send(&release.recipient, release.amount))
+        .add_attributes([
+            ("action", "claim_release"),
+            ("recipient", release.recipient.as_str()),
+        ]);
+    Ok(res)
+}
+
+/* ExecuteMsg::UpdateCounterOffer associated function:
+- only the creator may update, and only while still holding the option (before it is sold or co-owned)
+- the new counter_offer must be non-empty and free of zero amount coins
+- old and new values are emitted in the attributes
+*/
+pub fn execute_update_counter_offer(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    counter_offer: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the creator
+    let mut state = load_option(deps.storage)?;
+    if info.sender != state.creator {
+        return Err(ContractError::Unauthorized {
+            action: "update_counter_offer".to_string(),
+        });
+    }
+    if state.owner != state.creator || !state.owners.is_empty() {
+        return Err(ContractError::AlreadyTransferred {});
+    }
+
+    if counter_offer.is_empty() {
+        return Err(ContractError::EmptyCounterOffer {});
+    }
+    validate::no_zero_amounts(&counter_offer)?;
+    validate::no_duplicate_denoms(&counter_offer)?;
+    let allowed_denoms = ALLOWED_DENOMS
+        .may_load(deps.storage)
+        .map_err(ContractError::storage_load)?
+        .unwrap_or_default();
+    validate::all_allowed(&counter_offer, &allowed_denoms)?;
+    if state.validate_ibc_denoms {
+        validate::ibc_denoms(&counter_offer)?;
+    }
+    validate_decay(
+        state.decay_bps_per_block,
+        &counter_offer,
+        state.min_counter_offer,
+    )?;
+
+    let old = coins_to_string(&state.counter_offer);
+    state.counter_offer = normalize_coins(counter_offer);
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new().add_attributes([
+        ("action", "update_counter_offer"),
+        ("old_counter_offer", old.as_str()),
+        (
+            "new_counter_offer",
+            coins_to_string(&state.counter_offer).as_str(),
+        ),
+    ]);
+    Ok(res)
+}
+
+/* ExecuteMsg::Renounce associated function:
+- only the current owner may renounce, and only if that owner is not the creator already
+- ownership goes back to the creator, who is then free to execute, transfer or burn as the sole party,
+  or to withdraw the collateral right away
+- a counter offer escrowed by the renouncing owner is returned to them
+*/
+pub fn execute_renounce(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the owner
+    let mut state = load_option(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            action: "renounce".to_string(),
+        });
+    }
+    if state.owner == state.creator {
+        return Err(ContractError::NothingToRenounce {});
+    }
+    if !state.owners.is_empty() {
+        return Err(ContractError::CoOwned {});
+    }
+
+    // hand the option back to the creator
+    state.owner = state.creator.clone();
+    state.approved = None;
+    state.renounced = true;
+    let escrow = std::mem::take(&mut state.escrow);
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new()
+        .add_messages(bank_send(&info.sender, escrow))
+        .add_attributes([("action", "renounce"), ("owner", state.owner.as_str())]);
+    Ok(res)
+}
+
+/* ExecuteMsg::Sweep associated function:
+- only the creator may sweep
+- queries the contract balance of the denom and keeps back whatever part of it is escrowed as collateral
+- the excess is sent to the creator
+*/
+pub fn execute_sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the creator
+    let state = load_option(deps.storage)?;
+    if info.sender != state.creator {
+        return Err(ContractError::Unauthorized {
+            action: "sweep".to_string(),
+        });
+    }
+
+    // escrowed collateral and counter_offer are never swept
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, denom.as_str())?;
+    let escrowed = aggregate_coins(&[state.collateral, state.escrow].concat())
+        .get(denom.as_str())
+        .copied()
+        .unwrap_or_default();
+    let excess = balance.amount.saturating_sub(escrowed);
+    if excess.is_zero() {
+        return Err(ContractError::NothingToSweep { denom });
+    }
+
+    let res = Response::new()
+        .add_messages(bank_send(&state.creator, coins(excess.u128(), &denom)))
+        .add_attributes([("action", "sweep"), ("denom", denom.as_str())])
+        .add_attribute("amount", excess.to_string());
+    Ok(res)
+}
+
+/* ExecuteMsg::ForceExpire associated function:
+- only the admin set at instantiate may force expiry, without an admin nobody can
+- sets state.expires to the current height, so burn is available immediately in this very block
+*/
+pub fn execute_force_expire(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the admin
+    let mut state = load_option(deps.storage)?;
+    if state.admin.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            action: "force_expire".to_string(),
+        });
+    }
+
+    // only ever brings expiry forward
+    state.expires = state.expires.min(env.block.height);
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new()
+        .add_attribute("action", "force_expire")
+        .add_attribute("expires", state.expires.to_string());
+    Ok(res)
+}
+
+/* ExecuteMsg::ProposeRescind associated function:
+- either the creator or the owner can propose to unwind an unexpired option
+- the proposer is stored in RESCIND until the other party accepts, a new proposal replaces it
+*/
+pub fn execute_propose_rescind(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is a party to the option
+    let state = load_option(deps.storage)?;
+    if info.sender != state.owner && info.sender != state.creator {
+        return Err(ContractError::Unauthorized {
+            action: "propose_rescind".to_string(),
+        });
+    }
+    assert_exclusive(&env, &state, Phase::Exercisable)?;
+
+    RESCIND
+        .save(deps.storage, &info.sender)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new().add_attributes([
+        ("action", "propose_rescind"),
+        ("proposer", info.sender.as_str()),
+    ]);
+    Ok(res)
+}
+
+/* ExecuteMsg::AcceptRescind associated function:
+- a proposal must exist, made by a party that is still creator or owner (a transfer voids the old owner's proposal)
+- only the other party may accept it
+- collateral is returned to the creator, any escrow to the owner, and the option is removed
+*/
+pub fn execute_accept_rescind(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is a party to the option
+    let state = load_option(deps.storage)?;
+    if info.sender != state.owner && info.sender != state.creator {
+        return Err(ContractError::Unauthorized {
+            action: "accept_rescind".to_string(),
+        });
+    }
+    assert_exclusive(&env, &state, Phase::Exercisable)?;
+
+    let proposer = RESCIND
+        .may_load(deps.storage)
+        .map_err(ContractError::storage_load)?
+        .filter(|p| *p == state.owner || *p == state.creator)
+        .ok_or(ContractError::NoRescindProposal {})?;
+    if proposer == info.sender {
+        return Err(ContractError::CannotSelfAccept {});
+    }
+
+    // ensure the contract actually holds the collateral it is about to release
+    assert_contract_holds(deps.as_ref(), &env, &state.collateral)?;
+
+    // return collateral to creator and the escrowed counter_offer to owner
+    let res = Response::new()
+        .add_messages(release_collateral(&state, &state.creator)?)
+        .add_messages(bank_send(&state.owner, state.escrow.clone()))
+        .add_attribute("action", "accept_rescind");
+
+    // delete the option and the proposal
+    CONFIG.remove(deps.storage);
+    RESCIND.remove(deps.storage);
+
+    Ok(res)
+}
+
+/* ExecuteMsg::Withdraw associated function:
+- only the creator may withdraw, and only while the option was never transferred nor co-owned
+- the option must not be expired yet (that is what burn is for), but within early_withdraw_window blocks of expiry,
+  unless it was renounced back to the creator, nobody else has a claim on it then
+- collateral and any escrow are returned to the creator and the option is removed
+*/
+pub fn execute_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the creator still holding the option
+    let state = load_option(deps.storage)?;
+    if info.sender != state.creator {
+        return Err(ContractError::Unauthorized {
+            action: "withdraw".to_string(),
+        });
+    }
+    if state.owner != state.creator || !state.owners.is_empty() {
+        return Err(ContractError::AlreadyTransferred {});
+    }
+    assert_exclusive(&env, &state, Phase::Exercisable)?;
+
+    // ensure the window before expiry is open, a zero window never opens
+    let opens = state.expires.saturating_sub(state.early_withdraw_window);
+    if !state.renounced && (state.early_withdraw_window == 0 || env.block.height < opens) {
+        return Err(ContractError::WithdrawWindowNotOpen { opens });
+    }
+
+    // ensure the contract actually holds the collateral it is about to release
+    assert_contract_holds(deps.as_ref(), &env, &state.collateral)?;
+
+    // return collateral and escrow to creator
+    let mut msgs = release_collateral(&state, &state.creator)?;
+    msgs.extend(bank_send(&state.creator, state.escrow.clone()).map(CosmosMsg::from));
+    let res = Response::new()
+        .add_messages(split_sends(&state, msgs))
+        .add_attribute("action", "withdraw");
+
+    // delete the option
+    CONFIG.remove(deps.storage);
+
+    Ok(res)
+}
+
+/* ExecuteMsg::PauseOption associated function:
+- only the creator may pause or resume, and only while the option was never transferred nor co-owned
+- a paused option cannot be exercised, burning it after expiry still works
+*/
+pub fn execute_pause_option(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the creator still holding the option
+    let mut state = load_option(deps.storage)?;
+    if info.sender != state.creator {
+        return Err(ContractError::Unauthorized {
+            action: "pause_option".to_string(),
+        });
+    }
+    if state.owner != state.creator || !state.owners.is_empty() {
+        return Err(ContractError::AlreadyTransferred {});
+    }
+
+    state.paused = paused;
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new()
+        .add_attributes([("action", "pause_option"), ("paused", &paused.to_string())]);
+    Ok(res)
+}
+
+/* ExecuteMsg::ProposeAdmin associated function:
+- only the current admin may propose, without an admin nobody can
+- the validated new_admin is stored in PENDING_ADMIN until accepted, a new proposal replaces it
+*/
+pub fn execute_propose_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_admin: String,
+) -> Result<Response, ContractError> {
+    // ensure msg sender is the admin
+    let state = load_option(deps.storage)?;
+    if state.admin.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            action: "propose_admin".to_string(),
+        });
+    }
+
+    let new_admin = validate_addr(deps.api, &new_admin)?;
+    PENDING_ADMIN
+        .save(deps.storage, &new_admin)
+        .map_err(ContractError::storage_save)?;
+
+    let res = Response::new().add_attributes([
+        ("action", "propose_admin"),
+        ("pending_admin", new_admin.as_str()),
+    ]);
+    Ok(res)
+}
+
+/* ExecuteMsg::AcceptAdmin associated function:
+- only the proposed admin may accept, which proves the address is reachable
+- the proposal is consumed and the sender becomes the admin
+*/
+pub fn execute_accept_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut state = load_option(deps.storage)?;
+    let pending = PENDING_ADMIN
+        .may_load(deps.storage)
+        .map_err(ContractError::storage_load)?
+        .ok_or(ContractError::NoAdminProposal {})?;
+
+    // ensure msg sender is the proposed admin
+    if info.sender != pending {
+        return Err(ContractError::Unauthorized {
+            action: "accept_admin".to_string(),
+        });
+    }
+
+    state.admin = Some(pending);
+    CONFIG
+        .save(deps.storage, &state)
+        .map_err(ContractError::storage_save)?;
+    PENDING_ADMIN.remove(deps.storage);
+
+    let res = Response::new()
+        .add_attributes([("action", "accept_admin"), ("admin", info.sender.as_str())]);
+    Ok(res)
+}
+
+/*
+messages releasing the whole collateral to recipient - the coins (if any) followed by the NFT (if any)
+*/
+fn release_collateral(state: &State, recipient: &Addr) -> StdResult<Vec<CosmosMsg>> {
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    msgs.extend(collateral_bank_msg(state, recipient).map(CosmosMsg::from));
+    msgs.extend(release_nft(state, recipient)?);
+    Ok(msgs)
+}
+
+// bank send of the coin collateral, skipped when there are no coins (as for an NFT-only option)
+fn collateral_bank_msg(state: &State, recipient: &Addr) -> Option<BankMsg> {
+    bank_send(recipient, state.collateral.clone())
+}
+
+// CW721 transfer of the NFT collateral, if one was posted
+fn release_nft(state: &State, recipient: &Addr) -> StdResult<Option<CosmosMsg>> {
+    state
+        .nft_collateral
+        .as_ref()
+        .map(|(contract, token_id)| {
+            Ok(WasmMsg::Execute {
+                contract_addr: contract.to_string(),
+                msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                    recipient: recipient.to_string(),
+                    token_id: token_id.clone(),
+                })?,
+                funds: vec![],
+            }
+            .into())
+        })
+        .transpose()
+}
+
+/*
+CONFIG.load for the execute paths - once the option was executed, burned or rescinded its state is gone,
+which is reported as OptionNotFound rather than a raw storage error
+*/
+fn load_option(storage: &dyn Storage) -> Result<State, ContractError> {
+    CONFIG
+        .may_load(storage)
+        .map_err(ContractError::storage_load)?
+        .ok_or(ContractError::OptionNotFound {})
+}
+
+// an address loaded from storage must still validate, e.g. after a bech32 prefix change
+fn assert_valid_stored(api: &dyn Api, addr: &Addr) -> Result<(), ContractError> {
+    api.addr_validate(addr.as_str())
+        .map(|_| ())
+        .map_err(|_| ContractError::InvalidStoredAddress {
+            address: addr.to_string(),
+        })
+}
+
+/*
+addr_validate wrapper keeping the rejected input in the error, so the user knows which address was wrong
+*/
+fn validate_addr(api: &dyn Api, input: &str) -> Result<Addr, ContractError> {
+    api.addr_validate(input)
+        .map_err(|source| ContractError::InvalidAddress {
+            input: input.to_string(),
+            source,
+        })
+}
+
+/*
+execute and burn must never both be possible in the same block. Both sides derive from the single
+is_expired comparison: execute while height < expires, burn from height == expires on
+*/
+enum Phase {
+    Exercisable,
+    Expired,
+}
+
+fn is_expired(env: &Env, state: &State) -> bool {
+    env.block.height >= state.expires
+}
+
+fn assert_exclusive(env: &Env, state: &State, phase: Phase) -> Result<(), ContractError> {
+    match (phase, is_expired(env, state)) {
+        (Phase::Exercisable, true) => Err(ContractError::OptionExpired {
+            expired: state.expires,
+        }),
+        (Phase::Expired, false) => Err(ContractError::OptionNotExpired {
+            expires: state.expires,
+        }),
+        _ => Ok(()),
+    }
+}
+
+// overpayment must stay within max_extra for every denom, denoms missing from max_extra allow none
+fn assert_within_tolerance(excess: &[Coin], max_extra: &[Coin]) -> Result<(), ContractError> {
+    let tolerance = aggregate_coins(max_extra);
+    for coin in excess {
+        let max_extra = tolerance
+            .get(coin.denom.as_str())
+            .copied()
+            .unwrap_or_default();
+        if coin.amount > max_extra {
+            return Err(ContractError::ExceedsTolerance {
+                denom: coin.denom.clone(),
+                extra: coin.amount,
+                max_extra,
+            });
+        }
+    }
+    Ok(())
+}
+
+/*
+helper comparing two coin lists as sets - both sides are aggregated per denom (sorted, duplicates summed)
+so [ETH, BTC] equals [BTC, ETH] and [ETH 1, ETH 1] equals [ETH 2]
+*/
+pub fn coins_equal_unordered(a: &[Coin], b: &[Coin]) -> bool {
+    aggregate_coins(a) == aggregate_coins(b)
+}
+
+/*
+helper splitting the sent funds into the counter_offer and whatever was paid on top of it - overpaid
+counter_offer denoms as well as denoms outside of the counter_offer, all of which are refunded.
+Returns None if a counter_offer denom is underpaid
+*/
+fn counter_offer_excess(funds: &[Coin], counter_offer: &[Coin]) -> Option<Vec<Coin>> {
+    let required = aggregate_coins(counter_offer);
+    let paid = aggregate_coins(funds);
+    for (denom, due) in &required {
+        if paid.get(denom).copied().unwrap_or_default() < *due {
+            return None;
+        }
+    }
+
+    let excess = paid
+        .into_iter()
+        .filter_map(|(denom, amount)| {
+            let extra = amount - required.get(denom).copied().unwrap_or_default();
+            (!extra.is_zero()).then(|| Coin {
+                denom: denom.to_string(),
+                amount: extra,
+            })
+        })
+        .collect();
+    Some(excess)
+}
+
+// denoms of funds the counter_offer does not ask for, sorted and deduplicated
+fn unexpected_denoms(funds: &[Coin], counter_offer: &[Coin]) -> Vec<String> {
+    let required = aggregate_coins(counter_offer);
+    aggregate_coins(funds)
+        .into_keys()
+        .filter(|denom| !required.contains_key(denom))
+        .map(str::to_string)
+        .collect()
+}
+
+// from minus sub per denom, keeping the order of from and dropping coins that reach zero -
+// None when sub is not covered by from
+fn subtract_coins(from: &[Coin], sub: &[Coin]) -> Option<Vec<Coin>> {
+    let mut due = aggregate_coins(sub);
+    let mut rest = vec![];
+    for coin in from {
+        let taken = match due.get_mut(coin.denom.as_str()) {
+            Some(due) => {
+                let taken = (*due).min(coin.amount);
+                *due -= taken;
+                taken
+            }
+            None => Uint128::zero(),
+        };
+        let left = coin.amount - taken;
+        if !left.is_zero() {
+            rest.push(Coin {
+                denom: coin.denom.clone(),
+                amount: left,
+            });
+        }
+    }
+    due.values().all(|due| due.is_zero()).then_some(rest)
+}
+
+fn aggregate_coins(coins: &[Coin]) -> BTreeMap<&str, Uint128> {
+    let mut sums = BTreeMap::new();
+    for coin in coins {
+        *sums
+            .entry(coin.denom.as_str())
+            .or_insert_with(Uint128::zero) += coin.amount;
+    }
+    sums
+}
+
+/*
+the collateral has to hold at least min in every denom of it, as expected by the executor
+*/
+fn assert_collateral_out(collateral: &[Coin], min: &[Coin]) -> Result<(), ContractError> {
+    let held = aggregate_coins(collateral);
+    for (denom, expected) in aggregate_coins(min) {
+        let actual = held.get(denom).copied().unwrap_or_default();
+        if actual < expected {
+            return Err(ContractError::CollateralBelowExpected {
+                denom: denom.to_string(),
+                expected,
+                held: actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+/*
+coins sorted by denom with duplicates summed - the form collateral and counter_offer are stored in
+*/
+pub fn normalize_coins(coins: Vec<Coin>) -> Vec<Coin> {
+    aggregate_coins(&coins)
+        .into_iter()
+        .map(|(denom, amount)| Coin {
+            denom: denom.to_string(),
+            amount,
+        })
+        .collect()
+}
+
+/*
+fee and fraction math - every multiplication is checked and surfaces ContractError::Overflow
+instead of panicking on large amounts. Results are rounded down
+*/
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+pub fn mul_bps(amount: Uint128, bps: u16) -> Result<Uint128, ContractError> {
+    amount
+        .checked_mul(Uint128::from(bps))
+        .map_err(|_| ContractError::Overflow {})?
+        .checked_div(Uint128::new(BPS_DENOMINATOR))
+        .map_err(|_| ContractError::Overflow {})
+}
+
+/*
+counter_offer due at the current height - with decay_bps_per_block set, the single denom counter_offer
+is discounted linearly by decay_bps_per_block of itself per block since decay_start,
+never going below min_counter_offer (capped at the full counter_offer). The result is clamped into the collar
+*/
+pub fn effective_counter_offer(state: &State, env: &Env) -> Result<Vec<Coin>, ContractError> {
+    Ok(collar(decayed_counter_offer(state, env)?, state))
+}
+
+/*
+counter_offer clamped per denom between floor and cap, denoms without a bound are left alone
+*/
+fn collar(counter_offer: Vec<Coin>, state: &State) -> Vec<Coin> {
+    let bound = |bounds: &Option<Vec<Coin>>, denom: &str| {
+        bounds
+            .iter()
+            .flatten()
+            .find(|bound| bound.denom == denom)
+            .map(|bound| bound.amount)
+    };
+    counter_offer
+        .into_iter()
+        .map(|mut coin| {
+            if let Some(floor) = bound(&state.floor, &coin.denom) {
+                coin.amount = coin.amount.max(floor);
+            }
+            if let Some(cap) = bound(&state.cap, &coin.denom) {
+                coin.amount = coin.amount.min(cap);
+            }
+            coin
+        })
+        .collect()
+}
+
+fn decayed_counter_offer(state: &State, env: &Env) -> Result<Vec<Coin>, ContractError> {
+    if state.decay_bps_per_block == 0 {
+        return Ok(state.counter_offer.clone());
+    }
+
+    // single denom, validated at instantiate
+    let base = &state.counter_offer[0];
+    let blocks = env.block.height.saturating_sub(state.decay_start);
+    let decay = base
+        .amount
+        .checked_multiply_ratio(
+            u128::from(state.decay_bps_per_block) * u128::from(blocks),
+            BPS_DENOMINATOR,
+        )
+        .map_err(|_| ContractError::Overflow {})?;
+    let floor = state.min_counter_offer.unwrap_or_default().min(base.amount);
+    Ok(vec![Coin {
+        denom: base.denom.clone(),
+        amount: base.amount.saturating_sub(decay).max(floor),
+    }])
+}
+
+/*
+counter_offer due for funds at the current height - the effective counter_offer, unless it is paid in a single
+denom it does not match as is and the creator configured denom_ratios converting every demanded denom
+into that one, in which case the converted single coin is due instead.
+Denoms paid within their configured tolerance of the due amount are due as paid
+*/
+fn due_counter_offer(state: &State, env: &Env, funds: &[Coin]) -> Result<Vec<Coin>, ContractError> {
+    let counter_offer = effective_counter_offer(state, env)?;
+    if let [payment] = funds {
+        if !state.denom_ratios.is_empty() && counter_offer_excess(funds, &counter_offer).is_none() {
+            if let Some(amount) =
+                convert_counter_offer(&counter_offer, &payment.denom, &state.denom_ratios)?
+            {
+                let converted = coins(amount.u128(), &payment.denom);
+                return Ok(within_tolerance(converted, funds, &state.tolerance));
+            }
+        }
+    }
+    Ok(within_tolerance(counter_offer, funds, &state.tolerance))
+}
+
+/*
+client rounding of micro-unit amounts - a denom paid within ±tolerance of its due amount is settled at the
+paid amount, forwarding exactly what was sent. Payments outside of it keep the due amount
+(failing when short, refunding the rest when over). No tolerance means strict matching
+*/
+fn within_tolerance(due: Vec<Coin>, funds: &[Coin], tolerance: &[Coin]) -> Vec<Coin> {
+    let paid = aggregate_coins(funds);
+    let tolerance = aggregate_coins(tolerance);
+    due.into_iter()
+        .map(|coin| {
+            let tolerance = tolerance
+                .get(coin.denom.as_str())
+                .copied()
+                .unwrap_or_default();
+            let paid = paid.get(coin.denom.as_str()).copied().unwrap_or_default();
+            let off = if paid > coin.amount {
+                paid - coin.amount
+            } else {
+                coin.amount - paid
+            };
+            if off <= tolerance && !paid.is_zero() {
+                Coin {
+                    denom: coin.denom,
+                    amount: paid,
+                }
+            } else {
+                coin
+            }
+        })
+        .collect()
+}
+
+/*
+value of counter_offer in denom, each other denom converted at its configured (from, to, ratio) with to = denom,
+rounded up in favour of the creator. None when some demanded denom has no ratio into denom
+*/
+fn convert_counter_offer(
+    counter_offer: &[Coin],
+    denom: &str,
+    ratios: &[(String, String, Decimal)],
+) -> Result<Option<Uint128>, ContractError> {
+    let mut total = Uint128::zero();
+    for coin in counter_offer {
+        let amount = if coin.denom == denom {
+            coin.amount
+        } else {
+            match ratios
+                .iter()
+                .find(|(from, to, _)| *from == coin.denom && to == denom)
+            {
+                Some((_, _, ratio)) => mul_ratio_ceil(coin.amount, *ratio)?,
+                None => return Ok(None),
+            }
+        };
+        total = total
+            .checked_add(amount)
+            .map_err(|_| ContractError::Overflow {})?;
+    }
+    Ok(Some(total))
+}
+
+fn validate_decay(
+    decay_bps_per_block: u16,
+    counter_offer: &[Coin],
+    min_counter_offer: Option<Uint128>,
+) -> Result<(), ContractError> {
+    if decay_bps_per_block == 0 {
+        return Ok(());
+    }
+    if counter_offer.len() != 1 || min_counter_offer.unwrap_or_default().is_zero() {
+        return Err(ContractError::DecayCounterOffer {});
+    }
+    Ok(())
+}
+
+pub fn mul_ratio(amount: Uint128, ratio: Decimal) -> Result<Uint128, ContractError> {
+    amount
+        .checked_multiply_ratio(ratio.numerator(), ratio.denominator())
+        .map_err(|_| ContractError::Overflow {})
+}
+
+// same as mul_ratio, but rounded up
+pub fn mul_ratio_ceil(amount: Uint128, ratio: Decimal) -> Result<Uint128, ContractError> {
+    let numerator = Uint256::from(amount) * Uint256::from(ratio.numerator());
+    let denominator = Uint256::from(ratio.denominator());
+    let ceil = (numerator + denominator - Uint256::one()) / denominator;
+    Uint128::try_from(ceil).map_err(|_| ContractError::Overflow {})
+}
+
+// collateral must meet every configured per-denom minimum, a missing denom counts as zero provided
+fn assert_min_collateral(
+    min_collateral: &[Coin],
+    collateral: &[Coin],
+) -> Result<(), ContractError> {
+    let provided = aggregate_coins(collateral);
+    for (denom, required) in aggregate_coins(min_collateral) {
+        let provided = provided.get(denom).copied().unwrap_or_default();
+        if provided < required {
+            return Err(ContractError::CollateralBelowMinimum {
+                denom: denom.to_string(),
+                required,
+                provided,
+            });
+        }
+    }
+    Ok(())
+}
+
+/*
+last-line invariant before releasing collateral - the contract's actual bank balance must cover every denom,
+so an under-funded contract (collateral swept or never properly escrowed) fails with a clear error
+instead of a reverted BankMsg. All balances are fetched in a single query
+*/
+fn assert_contract_holds(deps: Deps, env: &Env, coins: &[Coin]) -> Result<(), ContractError> {
+    let balances = deps.querier.query_all_balances(&env.contract.address)?;
+    let held = aggregate_coins(&balances);
+    for (denom, amount) in aggregate_coins(coins) {
+        if held.get(denom).copied().unwrap_or_default() < amount {
+            return Err(ContractError::InsufficientContractBalance {
+                denom: denom.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/*
+status of the option as seen at the current block - expiry wins over pausing, pausing over ownership
+*/
+fn option_status(env: &Env, state: &State) -> OptionStatus {
+    if is_expired(env, state) {
+        OptionStatus::Expired
+    } else if state.paused {
+        OptionStatus::Paused
+    } else if state.owner != state.creator {
+        OptionStatus::Transferred
+    } else {
+        OptionStatus::Open
+    }
+}
+
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps, env)?),
+        QueryMsg::Escrow {} => to_binary(&query_escrow(deps, env)?),
+        QueryMsg::Quote { pay } => to_binary(&query_quote(deps, env, pay)?),
+        QueryMsg::BreakEven {} => to_binary(&query_break_even(deps)?),
+        QueryMsg::SimulateExecute { funds, sender } => {
+            to_binary(&query_simulate_execute(deps, env, funds, sender)?)
+        }
+        QueryMsg::Info {} => to_binary(&query_info(deps, env)?),
+        QueryMsg::LastAction {} => to_binary(&LAST_ACTION.may_load(deps.storage)?),
+        QueryMsg::Receipt { id } => to_binary(&RECEIPTS.may_load(deps.storage, id)?),
+        QueryMsg::Export {} => to_binary(&query_export(deps, env)?),
+    }
+}
+
+/*
+QueryMsg::Config - the economically meaningful part of the option, plus its status at the current block.
+Bookkeeping fields added over time are left out of the StateView, so consumers get a stable shape
+*/
+fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    Ok(StateView {
+        status: option_status(&env, &state),
+        creator: state.creator.to_string(),
+        owner: state.owner.to_string(),
+        collateral: state.collateral,
+        counter_offer: state.counter_offer,
+        expires: state.expires,
+        settlement: state.settlement,
+        nft_collateral: state
+            .nft_collateral
+            .map(|(contract, token_id)| (contract.to_string(), token_id)),
+        escrow: state.escrow,
+    })
+}
+
+/*
+QueryMsg::Info - which contract and version is running, and at which address
+*/
+fn query_info(deps: Deps, env: Env) -> StdResult<InfoResponse> {
+    let version = get_contract_version(deps.storage)?;
+    Ok(InfoResponse {
+        contract: version.contract,
+        version: version.version,
+        address: env.contract.address.to_string(),
+    })
+}
+
+/*
+QueryMsg::SimulateExecute - dry run of ExecuteMsg::Execute by sender with funds attached, against the current
+state and block. Nothing is written, failures are reported in the response instead of failing the query
+*/
+fn query_simulate_execute(
+    deps: Deps,
+    env: Env,
+    funds: Vec<Coin>,
+    sender: String,
+) -> StdResult<SimulateExecuteResponse> {
+    let state = with_denom_aliases(deps.storage, CONFIG.load(deps.storage)?, &funds)?;
+    let sender = deps.api.addr_validate(&sender)?;
+
+    let res = match simulate_execute(deps, &env, &state, &sender, &funds) {
+        Ok(msgs) => SimulateExecuteResponse {
+            ok: true,
+            error: None,
+            would_send: msgs
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                        Some((to_address, amount))
+                    }
+                    CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr,
+                        funds,
+                        ..
+                    }) => Some((contract_addr, funds)),
+                    _ => None,
+                })
+                .collect(),
+        },
+        Err(err) => SimulateExecuteResponse {
+            ok: false,
+            error: Some(err.to_string()),
+            would_send: vec![],
+        },
+    };
+    Ok(res)
+}
+
+fn simulate_execute(
+    deps: Deps,
+    env: &Env,
+    state: &State,
+    sender: &Addr,
+    funds: &[Coin],
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let msgs = match &state.settlement {
+        Settlement::Physical => {
+            compute_execute(state, env, sender, funds, &ExerciseParams::default())?
+        }
+        Settlement::CashSettled { oracle, strike } => {
+            check_exercise(state, env, sender, false)?;
+            let price = query_price(deps, oracle)?;
+            let (_, msgs) = compute_cash_settled(state, funds, &state.owner, price, *strike)?;
+            msgs.into_iter().map(CosmosMsg::from).collect()
+        }
+    };
+    assert_contract_holds(deps, env, &state.collateral)?;
+    Ok(msgs)
+}
+
+/*
+QueryMsg::BreakEven - the counter_offer as stored, and for cash settled options the strike together with
+the current oracle price, the holder breaking even once the price is above the strike
+*/
+fn query_break_even(deps: Deps) -> StdResult<BreakEvenResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    let (strike, current_price) = match &state.settlement {
+        Settlement::Physical => (None, None),
+        Settlement::CashSettled { oracle, strike } => {
+            (Some(*strike), Some(query_price(deps, oracle)?))
+        }
+    };
+    Ok(BreakEvenResponse {
+        required: state.counter_offer,
+        strike,
+        current_price,
+    })
+}
+
+/*
+QueryMsg::Quote - compares pay per denom against the counter_offer due right now (decayed or converted
+the same way Execute would), so a UI can show what is missing or would be refunded without a dry run
+*/
+fn query_quote(deps: Deps, env: Env, pay: Vec<Coin>) -> StdResult<QuoteResponse> {
+    let state = with_denom_aliases(deps.storage, CONFIG.load(deps.storage)?, &pay)?;
+    let counter_offer =
+        due_counter_offer(&state, &env, &pay).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let required = aggregate_coins(&counter_offer);
+    let paid = aggregate_coins(&pay);
+    let diff = |a: &BTreeMap<&str, Uint128>, b: &BTreeMap<&str, Uint128>| -> Vec<Coin> {
+        a.iter()
+            .filter_map(|(denom, amount)| {
+                let diff = amount.saturating_sub(b.get(denom).copied().unwrap_or_default());
+                (!diff.is_zero()).then(|| Coin {
+                    denom: denom.to_string(),
+                    amount: diff,
+                })
+            })
+            .collect()
+    };
+    let shortfall = diff(&required, &paid);
+    Ok(QuoteResponse {
+        sufficient: shortfall.is_empty(),
+        shortfall,
+        excess: diff(&paid, &required),
+    })
+}
+
+/*
+QueryMsg::Escrow - accounting view of the option, comparing the recorded collateral (together with
+any escrowed counter_offer) with what the contract really holds on chain
+*/
+fn query_escrow(deps: Deps, env: Env) -> StdResult<EscrowResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    let balance = deps.querier.query_all_balances(env.contract.address)?;
+    let held = [state.collateral.as_slice(), &state.escrow].concat();
+    Ok(EscrowResponse {
+        balance_matches: coins_equal_unordered(&balance, &held),
+        collateral: state.collateral,
+        counter_offer: state.counter_offer,
+    })
+}
+
+// a contract instance holds a single option, which is listed under this id
+const OPTION_ID: u64 = 0;
+
+/*
+QueryMsg::Export - flattens the stored option into a record of plain strings, ready to be written as a CSV row
+*/
+fn query_export(deps: Deps, env: Env) -> StdResult<ExportResponse> {
+    let options = CONFIG
+        .may_load(deps.storage)?
+        .map(|state| ExportRecord {
+            id: OPTION_ID,
+            creator: state.creator.to_string(),
+            owner: state.owner.to_string(),
+            expires: state.expires,
+            collateral_string: coins_to_string(&state.collateral),
+            counter_offer_string: coins_to_string(&state.counter_offer),
+            status: option_status(&env, &state),
+        })
+        .into_iter()
+        .collect();
+    Ok(ExportResponse { options })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{
+        attr, coin, from_binary, Attribute, ContractResult, CosmosMsg, QuerierResult, ReplyOn,
+        StdError, SubMsgResponse, SystemResult, WasmQuery,
+    };
+
+    // ordered (recipient, amount) of every bank send in the response
+    fn bank_sends(res: &Response) -> Vec<(String, Vec<Coin>)> {
+        res.messages
+            .iter()
+            .map(|sub| match &sub.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                    (to_address.clone(), amount.clone())
+                }
+                msg => panic!("unexpected message: {:?}", msg),
+            })
+            .collect()
+    }
+
+    fn assert_recipients(res: &Response, expected: &[&str]) {
+        let recipients: Vec<String> = bank_sends(res).into_iter().map(|(to, _)| to).collect();
+        assert_eq!(recipients, expected);
+    }
+
+    // every response must carry each attribute key (in particular action) only once
+    fn assert_unique_keys(attributes: &[Attribute]) {
+        let mut keys: Vec<&str> = attributes.iter().map(|a| a.key.as_str()).collect();
+        keys.sort_unstable();
+        if let Some(pair) = keys.windows(2).find(|pair| pair[0] == pair[1]) {
+            panic!("duplicate attribute key: {}", pair[0]);
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // the created option comes back as data
+        let data: State = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(data, CONFIG.load(&deps.storage).unwrap());
+        assert_eq!(data.decay_start, mock_env().block.height);
+
+        // it worked, let's query the state
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(100_000, res.expires);
+        assert_eq!("creator", res.owner.as_str());
+        assert_eq!("creator", res.creator.as_str());
+        assert_eq!(coins(1, "BTC"), res.collateral);
+        assert_eq!(coins(40, "ETH"), res.counter_offer);
+    }
+
+    #[test]
+    fn transfer() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // random cannot transfer
+        let info = mock_info("anyone", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), mock_env(), info, "anyone".to_string()).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "transfer"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // owner can transfer
+        let info = mock_info("creator", &[]);
+        let res = execute_transfer(deps.as_mut(), mock_env(), info, "someone".to_string()).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(res.attributes.len(), 2);
+        assert_eq!(res.attributes[0], attr("action", "transfer"));
+
+        // check updated properly
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!("someone", res.owner.as_str());
+        assert_eq!("creator", res.creator.as_str());
+    }
+
+    #[test]
+    fn execute() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let amount = coins(40, "ETH");
+        let collateral = coins(1, "BTC");
+        let expires = 100_000;
+        let msg = InstantiateMsg {
+            counter_offer: amount.clone(),
+            expires,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &collateral);
+
+        // we can just call .unwrap() to assert this was a success
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // set new owner
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // random cannot execute
+        let info = mock_info("creator", &amount);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "execute"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // expired cannot execute
+        let info = mock_info("owner", &amount);
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let err = execute_execute(deps.as_mut(), env, info, ExerciseParams::default()).unwrap_err();
+        match err {
+            ContractError::OptionExpired { expired } => assert_eq!(expired, expires),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // bad counter_offer cannot execute
+        let msg_offer = coins(39, "ETH");
+        let info = mock_info("owner", &msg_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::CounterOfferMismatch {
+                offer,
+                counter_offer,
+            } => {
+                assert_eq!(msg_offer, offer);
+                assert_eq!(amount, counter_offer);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // proper execution
+        let info = mock_info("owner", &amount);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount,
+            })
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".into(),
+                amount: collateral,
+            })
+        );
+
+        // check deleted
+        let _ = query_config(deps.as_ref(), mock_env()).unwrap_err();
+    }
+
+    #[test]
+    fn burn() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let collateral = coins(1, "BTC");
+        let msg_expires = 100_000;
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: msg_expires,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &collateral);
+
+        // we can just call .unwrap() to assert this was a success
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // set new owner
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // non-expired cannot execute
+        let info = mock_info("anyone", &[]);
+        let err = execute_burn(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::OptionNotExpired { expires } => assert_eq!(expires, msg_expires),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // with funds cannot execute
+        let info = mock_info("anyone", &counter_offer);
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let err = execute_burn(deps.as_mut(), env, info).unwrap_err();
+        match err {
+            ContractError::FundsSentWithBurn {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // expired returns funds
+        let info = mock_info("anyone", &[]);
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let res = execute_burn(deps.as_mut(), env, info).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: collateral,
+            })
+        );
+
+        // check deleted
+        let _ = query_config(deps.as_ref(), mock_env()).unwrap_err();
+    }
+
+    #[test]
+    fn overlapping_denoms() {
+        let mut deps = mock_dependencies();
+
+        // strict mode rejects a denom present on both sides
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+            expires: 100_000,
+            strict_denoms: true,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(2, "ETH")]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::OverlappingDenoms { denom } => assert_eq!(denom, "ETH"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // strict mode accepts disjoint denoms
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            strict_denoms: true,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // overlap is allowed when not strict
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            strict_denoms: false,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(2, "ETH"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(coins(2, "ETH"), res.collateral);
+    }
+
+    #[test]
+    fn execute_with_unordered_funds() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = vec![coin(40, "ETH"), coin(5, "ATOM")];
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // different amounts in reversed order still mismatch
+        let bad_offer = vec![coin(5, "ATOM"), coin(39, "ETH")];
+        let info = mock_info("creator", &bad_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::CounterOfferMismatch { offer, .. } => assert_eq!(offer, bad_offer),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // same coins in reversed order match, the creator is paid the stored (sorted) counter_offer
+        let info = mock_info("creator", &[coin(5, "ATOM"), coin(40, "ETH")]);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: vec![coin(5, "ATOM"), coin(40, "ETH")],
+            })
+        );
+    }
+
+    #[test]
+    fn unordered_coin_equality() {
+        assert!(coins_equal_unordered(
+            &[coin(1, "BTC"), coin(2, "ETH")],
+            &[coin(2, "ETH"), coin(1, "BTC")]
+        ));
+        assert!(coins_equal_unordered(
+            &[coin(1, "ETH"), coin(1, "ETH")],
+            &coins(2, "ETH")
+        ));
+        assert!(!coins_equal_unordered(&coins(2, "ETH"), &coins(3, "ETH")));
+        assert!(!coins_equal_unordered(
+            &coins(2, "ETH"),
+            &[coin(2, "ETH"), coin(1, "BTC")]
+        ));
+        assert!(coins_equal_unordered(&[], &[]));
+    }
+
+    #[test]
+    fn export() {
+        let mut deps = mock_dependencies();
+
+        // nothing to export before instantiation
+        let res = query_export(deps.as_ref(), mock_env()).unwrap();
+        assert!(res.options.is_empty());
+
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(2, "OSMO")]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query_export(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(
+            res.options,
+            vec![ExportRecord {
+                id: 0,
+                creator: "creator".to_string(),
+                owner: "creator".to_string(),
+                expires: 100_000,
+                collateral_string: "1BTC,2OSMO".to_string(),
+                counter_offer_string: "5ATOM,40ETH".to_string(),
+                status: OptionStatus::Open,
+            }]
+        );
+
+        // status follows transfer and expiry
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "someone".to_string()).unwrap();
+        let res = query_export(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(res.options[0].owner, "someone");
+        assert_eq!(res.options[0].status, OptionStatus::Transferred);
+
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let res = query_export(deps.as_ref(), env).unwrap();
+        assert_eq!(res.options[0].status, OptionStatus::Expired);
+    }
+
+    #[test]
+    fn state_view() {
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // freshly instantiated
+        let mut deps = setup();
+        let res = query_config(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(
+            res,
+            StateView {
+                creator: "creator".to_string(),
+                owner: "creator".to_string(),
+                collateral: coins(1, "BTC"),
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                settlement: Settlement::Physical,
+                nft_collateral: None,
+                escrow: vec![],
+                status: OptionStatus::Open,
+            }
+        );
+
+        // transferred
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "someone".to_string()).unwrap();
+        let res = query_config(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(res.owner, "someone");
+        assert_eq!(res.status, OptionStatus::Transferred);
+
+        // expired
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let res = query_config(deps.as_ref(), env).unwrap();
+        assert_eq!(res.status, OptionStatus::Expired);
+
+        // paused
+        let mut deps = setup();
+        let info = mock_info("creator", &[]);
+        execute_pause_option(deps.as_mut(), mock_env(), info, true).unwrap();
+        let res = query_config(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(res.status, OptionStatus::Paused);
+    }
+
+    #[test]
+    fn insufficient_contract_balance() {
+        // contract only holds 1 of the 2 BTC recorded as collateral
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(2, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &counter_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::InsufficientContractBalance { denom } => assert_eq!(denom, "BTC"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("anyone", &[]);
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let err = execute_burn(deps.as_mut(), env, info).unwrap_err();
+        match err {
+            ContractError::InsufficientContractBalance { denom } => assert_eq!(denom, "BTC"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // option is kept until it can actually be released
+        CONFIG.load(&deps.storage).unwrap();
+    }
+
+    #[test]
+    fn renounce() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // creator still holds the option, nothing to renounce
+        let info = mock_info("creator", &[]);
+        let err = execute_renounce(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::NothingToRenounce {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "holder".to_string()).unwrap();
+
+        // only the holder can renounce
+        let info = mock_info("anyone", &[]);
+        let err = execute_renounce(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "renounce"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("holder", &[]);
+        let res = execute_renounce(deps.as_mut(), mock_env(), info).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(res.attributes[0], attr("action", "renounce"));
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!("creator", res.owner.as_str());
+
+        // holder lost the claim, creator is in control again
+        let info = mock_info("holder", &counter_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "execute"),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &counter_offer);
+        execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+    }
+
+    #[test]
+    fn withdraw_renounced() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        // no early_withdraw_window, so the creator could never withdraw otherwise
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "holder".to_string()).unwrap();
+        let info = mock_info("creator", &[]);
+        let err = execute_withdraw(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::AlreadyTransferred {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // holder renounces, creator reclaims the collateral straight away
+        let info = mock_info("holder", &[]);
+        execute_renounce(deps.as_mut(), mock_env(), info).unwrap();
+        assert!(CONFIG.load(&deps.storage).unwrap().renounced);
+        let info = mock_info("creator", &[]);
+        let res = execute_withdraw(deps.as_mut(), mock_env(), info).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(1, "BTC"),
+            })]
+        );
+        assert!(CONFIG.may_load(&deps.storage).unwrap().is_none());
+
+        // transferring a renounced option again takes the early withdrawal away
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "holder".to_string()).unwrap();
+        let info = mock_info("holder", &[]);
+        execute_renounce(deps.as_mut(), mock_env(), info).unwrap();
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "other".to_string()).unwrap();
+        let info = mock_info("other", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "creator".to_string()).unwrap();
+        assert!(!CONFIG.load(&deps.storage).unwrap().renounced);
+        let info = mock_info("creator", &[]);
+        let err = execute_withdraw(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::WithdrawWindowNotOpen { opens } => assert_eq!(opens, 100_000),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn escrow() {
+        let mut deps = mock_dependencies_with_balance(&[coin(1, "BTC"), coin(2, "OSMO")]);
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(2, "OSMO"), coin(1, "BTC")]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query_escrow(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(vec![coin(1, "BTC"), coin(2, "OSMO")], res.collateral);
+        assert_eq!(coins(40, "ETH"), res.counter_offer);
+        assert!(res.balance_matches);
+
+        // balance drifting from the recorded collateral is reported
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, vec![coin(1, "BTC")]);
+        let res = query_escrow(deps.as_ref(), mock_env()).unwrap();
+        assert!(!res.balance_matches);
+
+        // an escrowed counter_offer is held on top of the collateral
+        let info = mock_info("creator", &coins(40, "ETH"));
+        execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap();
+        deps.querier.update_balance(
+            MOCK_CONTRACT_ADDR,
+            vec![coin(1, "BTC"), coin(2, "OSMO"), coin(40, "ETH")],
+        );
+        let res = query_escrow(deps.as_ref(), mock_env()).unwrap();
+        assert!(res.balance_matches);
+    }
+
+    #[test]
+    fn transfer_whitelist() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            transfer_whitelist: Some(vec!["desk1".to_string(), "desk2".to_string()]),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // non-listed recipient is rejected
+        let info = mock_info("creator", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), mock_env(), info, "outsider".to_string()).unwrap_err();
+        match err {
+            ContractError::RecipientNotWhitelisted { recipient } => {
+                assert_eq!(recipient, "outsider")
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // whitelisted recipient succeeds
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "desk2".to_string()).unwrap();
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!("desk2", res.owner.as_str());
+
+        // an empty whitelist leaves transfers unrestricted
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            transfer_whitelist: Some(vec![]),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(None, CONFIG.load(&deps.storage).unwrap().transfer_whitelist);
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "outsider".to_string()).unwrap();
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!("outsider", res.owner.as_str());
+    }
+
+    #[test]
+    fn execute_and_burn_result_data() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = vec![coin(40, "ETH"), coin(5, "ATOM")];
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        let info = mock_info("owner", &counter_offer);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        let data: ExecuteResult = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(
+            data,
+            ExecuteResult {
+                counter_offer: normalize_coins(counter_offer),
+                collateral: coins(1, "BTC"),
+                collateral_recipient: "owner".to_string(),
+            }
+        );
+
+        // burn reports only the released collateral
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let res = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+        let data: ExecuteResult = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(
+            data,
+            ExecuteResult {
+                counter_offer: vec![],
+                collateral: coins(1, "BTC"),
+                collateral_recipient: "creator".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn zero_amount_coins() {
+        let mut deps = mock_dependencies();
+
+        // zero-amount collateral coin
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(0, "OSMO")]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::ZeroAmountCoin { denom } => assert_eq!(denom, "OSMO"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // zero-amount counter_offer coin
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(0, "ATOM")],
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::ZeroAmountCoin { denom } => assert_eq!(denom, "ATOM"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    fn mock_oracle(price: u128) -> impl Fn(&WasmQuery) -> QuerierResult {
+        move |query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "oracle" => {
+                let res = PriceResponse {
+                    price: Uint128::new(price),
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            q => panic!("unexpected query: {:?}", q),
+        }
+    }
+
+    #[test]
+    fn break_even() {
+        // physically settled, just the counter_offer
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = query_break_even(deps.as_ref()).unwrap();
+        assert_eq!(
+            res,
+            BreakEvenResponse {
+                required: coins(40, "ETH"),
+                strike: None,
+                current_price: None,
+            }
+        );
+
+        // cash settled, with the strike and the oracle price
+        let mut deps = mock_dependencies_with_balance(&coins(100, "USDC"));
+        let msg = InstantiateMsg {
+            expires: 100_000,
+            settlement: Settlement::CashSettled {
+                oracle: Addr::unchecked("oracle"),
+                strike: Uint128::new(1_000),
+            },
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(100, "USDC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        deps.querier.update_wasm(mock_oracle(1_030));
+        let res = query_break_even(deps.as_ref()).unwrap();
+        assert_eq!(
+            res,
+            BreakEvenResponse {
+                required: vec![],
+                strike: Some(Uint128::new(1_000)),
+                current_price: Some(Uint128::new(1_030)),
+            }
+        );
+    }
+
+    #[test]
+    fn cash_settled() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "BTC"));
+
+        let msg = InstantiateMsg {
+            expires: 100_000,
+            settlement: Settlement::CashSettled {
+                oracle: Addr::unchecked("oracle"),
+                strike: Uint128::new(10),
+            },
+            ..Default::default()
+        };
+
+        // multi-denom collateral cannot be cash settled
+        let info = mock_info("creator", &[coin(100, "BTC"), coin(1, "ETH")]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        match err {
+            ContractError::CashSettledCollateral {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("creator", &coins(100, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // price below strike cannot execute
+        deps.querier.update_wasm(mock_oracle(9));
+        let info = mock_info("creator", &[]);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::OutOfTheMoney { price, strike } => {
+                assert_eq!(price, Uint128::new(9));
+                assert_eq!(strike, Uint128::new(10));
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // at 50 each of the 100 BTC is 40 above the strike, 4000 in quote units, worth 80 BTC
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+        deps.querier.update_wasm(mock_oracle(50));
+        let info = mock_info("owner", &[]);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("owner".to_string(), coins(80, "BTC")),
+                ("creator".to_string(), coins(20, "BTC")),
+            ]
+        );
+
+        // check deleted
+        let _ = query_config(deps.as_ref(), mock_env()).unwrap_err();
+
+        // the payout is rounded down in favour of the creator, 100 * 30 / 1030 BTC
+        let msg = InstantiateMsg {
+            expires: 100_000,
+            settlement: Settlement::CashSettled {
+                oracle: Addr::unchecked("oracle"),
+                strike: Uint128::new(1_000),
+            },
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(100, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        deps.querier.update_wasm(mock_oracle(1_030));
+        let info = mock_info("creator", &[]);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(2, "BTC")),
+                ("creator".to_string(), coins(98, "BTC")),
+            ]
+        );
+    }
+
+    #[test]
+    fn execute_burn_boundary() {
+        let counter_offer = coins(40, "ETH");
+        let expires = 100_000;
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: counter_offer.clone(),
+                expires,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // at height == expires execute fails and burn succeeds
+        let mut env = mock_env();
+        env.block.height = expires;
+        let mut deps = setup();
+        let info = mock_info("creator", &counter_offer);
+        let err = execute_execute(deps.as_mut(), env.clone(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::OptionExpired { expired } => assert_eq!(expired, expires),
+            e => panic!("unexpected error: {}", e),
+        }
+        execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+
+        // at height == expires - 1 burn fails and execute succeeds
+        let mut env = mock_env();
+        env.block.height = expires - 1;
+        let mut deps = setup();
+        let err = execute_burn(deps.as_mut(), env.clone(), mock_info("anyone", &[])).unwrap_err();
+        match err {
+            ContractError::OptionNotExpired { expires: e } => assert_eq!(e, expires),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &counter_offer);
+        execute_execute(deps.as_mut(), env, info, ExerciseParams::default()).unwrap();
+    }
+
+    #[test]
+    fn sweep() {
+        let mut deps = mock_dependencies_with_balance(&[coin(3, "BTC"), coin(5, "OSMO")]);
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // only creator can sweep
+        let info = mock_info("anyone", &[]);
+        let err = execute_sweep(deps.as_mut(), mock_env(), info, "BTC".to_string()).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "sweep"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // balance above collateral, only the excess is swept
+        let info = mock_info("creator", &[]);
+        let res = execute_sweep(deps.as_mut(), mock_env(), info, "BTC".to_string()).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(2, "BTC"),
+            })
+        );
+
+        // denom that is not collateral at all is swept entirely
+        let info = mock_info("creator", &[]);
+        let res = execute_sweep(deps.as_mut(), mock_env(), info, "OSMO".to_string()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(5, "OSMO"),
+            })
+        );
+
+        // balance equal to collateral, nothing to sweep
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(1, "BTC"));
+        let info = mock_info("creator", &[]);
+        let err = execute_sweep(deps.as_mut(), mock_env(), info, "BTC".to_string()).unwrap_err();
+        match err {
+            ContractError::NothingToSweep { denom } => assert_eq!(denom, "BTC"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn invalid_address() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let err = execute_transfer(deps.as_mut(), mock_env(), info, "x".to_string()).unwrap_err();
+        match err {
+            ContractError::InvalidAddress { input, .. } => assert_eq!(input, "x"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // whitelist entries are checked the same way
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            transfer_whitelist: Some(vec!["desk1".to_string(), "x".to_string()]),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::InvalidAddress { input, .. } => assert_eq!(input, "x"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn burn_beneficiary() {
+        for (beneficiary, recipient) in [
+            (BurnBeneficiary::Creator, "creator"),
+            (BurnBeneficiary::Owner, "owner"),
+        ] {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                burn_beneficiary: beneficiary.clone(),
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            assert_eq!(
+                beneficiary,
+                CONFIG.load(&deps.storage).unwrap().burn_beneficiary
+            );
+
+            let info = mock_info("creator", &[]);
+            execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+            let mut env = mock_env();
+            env.block.height = 200_000;
+            let res = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+            assert_eq!(
+                res.messages[0].msg,
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: recipient.into(),
+                    amount: coins(1, "BTC"),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn burn_mode() {
+        let setup = |burn_mode: BurnMode| {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                burn_mode,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+        let mut env = mock_env();
+        env.block.height = 200_000;
+
+        // creator only: an outsider cannot burn, the creator can
+        let mut deps = setup(BurnMode::CreatorOnly);
+        let err = execute_burn(deps.as_mut(), env.clone(), mock_info("anyone", &[])).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "burn"),
+            e => panic!("unexpected error: {}", e),
+        }
+        execute_burn(deps.as_mut(), env.clone(), mock_info("creator", &[])).unwrap();
+
+        // anyone: an outsider can burn
+        let mut deps = setup(BurnMode::Anyone);
+        execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+    }
+
+    #[test]
+    fn auto_burn_on_expired() {
+        let setup = |auto_burn_on_expired: bool| {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                auto_burn_on_expired,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let info = mock_info("creator", &[]);
+            execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+            deps
+        };
+        let mut env = mock_env();
+        env.block.height = 200_000;
+
+        // without the flag the usual error is returned
+        let mut deps = setup(false);
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let err = execute_execute(deps.as_mut(), env.clone(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::OptionExpired { expired } => assert_eq!(expired, 100_000),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // with it the option is burned, the collateral reverts and the payment is refunded
+        let mut deps = setup(true);
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let res = execute_execute(deps.as_mut(), env, info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(1, "BTC")),
+                ("owner".to_string(), coins(40, "ETH")),
+            ]
+        );
+        assert_eq!(res.attributes, vec![attr("action", "auto_burn")]);
+        let _ = query_config(deps.as_ref(), mock_env()).unwrap_err();
+    }
+
+    #[test]
+    fn transfer_to_current_owner() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        execute_approve(deps.as_mut(), mock_env(), info, "spender".to_string()).unwrap();
+        let before = CONFIG.load(&deps.storage).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let res = execute_transfer(deps.as_mut(), mock_env(), info, "creator".to_string()).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "transfer_noop"), attr("owner", "creator")]
+        );
+        // the approval survives, nothing was written
+        assert_eq!(CONFIG.load(&deps.storage).unwrap(), before);
+    }
+
+    #[test]
+    fn rescind() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // nothing proposed yet
+        let info = mock_info("owner", &[]);
+        let err = execute_accept_rescind(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::NoRescindProposal {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // outsiders cannot propose
+        let info = mock_info("anyone", &[]);
+        let err = execute_propose_rescind(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "propose_rescind"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("owner", &[]);
+        execute_propose_rescind(deps.as_mut(), mock_env(), info).unwrap();
+
+        // proposer cannot accept their own proposal
+        let info = mock_info("owner", &[]);
+        let err = execute_accept_rescind(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::CannotSelfAccept {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the other party accepts, collateral goes back to the creator
+        let info = mock_info("creator", &[]);
+        let res = execute_accept_rescind(deps.as_mut(), mock_env(), info).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(res.attributes[0], attr("action", "accept_rescind"));
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(1, "BTC"),
+            })
+        );
+
+        // check deleted
+        let _ = query_config(deps.as_ref(), mock_env()).unwrap_err();
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate attribute key: action")]
+    fn duplicate_attribute_keys() {
+        assert_unique_keys(&[
+            attr("action", "execute"),
+            attr("owner", "someone"),
+            attr("action", "burn"),
+        ]);
+    }
+
+    #[test]
+    fn execute_with_overpayment() {
+        let counter_offer = vec![coin(40, "ETH"), coin(5, "ATOM")];
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: counter_offer.clone(),
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // exact payment, no refund
+        let mut deps = setup();
+        let info = mock_info("creator", &counter_offer);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 2);
+
+        // overpayment in an existing denom is refunded
+        let mut deps = setup();
+        let info = mock_info("creator", &[coin(42, "ETH"), coin(5, "ATOM")]);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: normalize_coins(counter_offer.clone()),
+            })
+        );
+        assert_eq!(
+            res.messages[2].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(2, "ETH"),
+            })
+        );
+
+        // an unrelated bonus denom is refunded, the counter_offer alone goes to the creator
+        let mut deps = setup();
+        let info = mock_info(
+            "creator",
+            &[coin(40, "ETH"), coin(5, "ATOM"), coin(1, "OSMO")],
+        );
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                (
+                    "creator".to_string(),
+                    normalize_coins(counter_offer.clone())
+                ),
+                ("creator".to_string(), coins(1, "BTC")),
+                ("creator".to_string(), coins(1, "OSMO")),
+            ]
+        );
+
+        // together with an overpaid denom, both are refunded
+        let mut deps = setup();
+        let info = mock_info(
+            "creator",
+            &[coin(1, "OSMO"), coin(5, "ATOM"), coin(41, "ETH")],
+        );
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res)[2],
+            ("creator".to_string(), vec![coin(1, "ETH"), coin(1, "OSMO")])
+        );
+
+        // a bonus denom does not make up for an underpaid counter_offer, and is named in the error
+        let mut deps = setup();
+        let info = mock_info(
+            "creator",
+            &[coin(39, "ETH"), coin(5, "ATOM"), coin(100, "OSMO")],
+        );
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::UnexpectedDenoms { denoms } => assert_eq!(denoms, vec!["OSMO"]),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // paying in one wrong denom instead of ETH
+        let mut deps = setup();
+        let info = mock_info("creator", &[coin(40, "BTC"), coin(5, "ATOM")]);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::UnexpectedDenoms { denoms } => assert_eq!(denoms, vec!["BTC"]),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn info() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query_info(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(res.address, mock_env().contract.address.to_string());
+        assert_eq!(res.contract, CONTRACT_NAME);
+        assert_eq!(res.version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn expiry_horizon() {
+        let mut deps = mock_dependencies();
+        let height = mock_env().block.height;
+
+        // exactly the maximum lifetime is accepted
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: height + MAX_EXPIRY_BLOCKS,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // one block beyond is rejected
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: height + MAX_EXPIRY_BLOCKS + 1,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::ExpiryTooFar { max } => assert_eq!(max, MAX_EXPIRY_BLOCKS),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // far future expiry does not overflow
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: u64::MAX,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::ExpiryTooFar { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn approve() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // only owner can approve
+        let info = mock_info("market", &[]);
+        let err =
+            execute_approve(deps.as_mut(), mock_env(), info, "market".to_string()).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "approve"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // spender cannot transfer before being approved
+        let info = mock_info("market", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), mock_env(), info, "buyer".to_string()).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "transfer"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("creator", &[]);
+        let res = execute_approve(deps.as_mut(), mock_env(), info, "market".to_string()).unwrap();
+        assert_eq!(res.attributes[0], attr("action", "approve"));
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(Some(Addr::unchecked("market")), res.approved);
+
+        // approved spender transfers on the owner's behalf
+        let info = mock_info("market", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "buyer".to_string()).unwrap();
+
+        // approval is cleared afterwards
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!("buyer", res.owner.as_str());
+        assert_eq!(None, res.approved);
+
+        let info = mock_info("market", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), mock_env(), info, "market".to_string()).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "transfer"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn execute_to() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // still only the owner can execute
+        let info = mock_info("custodian", &counter_offer);
+        let err = execute_execute_to(deps.as_mut(), mock_env(), info, "custodian".to_string())
+            .unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "execute"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("owner", &counter_offer);
+        let res =
+            execute_execute_to(deps.as_mut(), mock_env(), info, "custodian".to_string()).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: counter_offer,
+            })
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "custodian".into(),
+                amount: coins(1, "BTC"),
+            })
+        );
+    }
+
+    #[test]
+    fn checked_fee_math() {
+        // rounds down
+        assert_eq!(
+            mul_bps(Uint128::new(1_001), 5_000).unwrap(),
+            Uint128::new(500)
+        );
+        assert_eq!(mul_bps(Uint128::new(1_000), 0).unwrap(), Uint128::zero());
+
+        // largest amount that can be multiplied by 10000 bps
+        let max = Uint128::new(u128::MAX / 10_000);
+        assert_eq!(mul_bps(max, 10_000).unwrap(), max);
+        match mul_bps(Uint128::MAX, 10_000).unwrap_err() {
+            ContractError::Overflow {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        match mul_bps(max + Uint128::new(1), 10_000).unwrap_err() {
+            ContractError::Overflow {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // ratios are computed without intermediate overflow
+        assert_eq!(
+            mul_ratio(Uint128::MAX, Decimal::percent(50)).unwrap(),
+            Uint128::new(u128::MAX / 2)
+        );
+        assert_eq!(
+            mul_ratio(Uint128::new(3), Decimal::percent(50)).unwrap(),
+            Uint128::new(1)
+        );
+        match mul_ratio(Uint128::MAX, Decimal::percent(200)).unwrap_err() {
+            ContractError::Overflow {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn execute_message_order() {
+        let counter_offer = coins(40, "ETH");
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: counter_offer.clone(),
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let info = mock_info("creator", &[]);
+            execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+            deps
+        };
+
+        // counter_offer to creator first, collateral to owner second
+        let mut deps = setup();
+        let info = mock_info("owner", &counter_offer);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_recipients(&res, &["creator", "owner"]);
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), counter_offer.clone()),
+                ("owner".to_string(), coins(1, "BTC")),
+            ]
+        );
+
+        // refund is appended after them
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(45, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_recipients(&res, &["creator", "owner", "owner"]);
+        assert_eq!(bank_sends(&res)[2], ("owner".to_string(), coins(5, "ETH")));
+
+        // directing the collateral keeps its slot
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(45, "ETH"));
+        let res = execute_execute_to(deps.as_mut(), mock_env(), info, "cold".to_string()).unwrap();
+        assert_recipients(&res, &["creator", "cold", "owner"]);
+    }
+
+    #[test]
+    fn force_expire() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            admin: Some("admin".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // non-admin cannot force expire
+        let info = mock_info("creator", &[]);
+        let err = execute_force_expire(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "force_expire"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("admin", &[]);
+        execute_force_expire(deps.as_mut(), mock_env(), info).unwrap();
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(mock_env().block.height, res.expires);
+
+        // burn succeeds in the same block
+        let res = execute_burn(deps.as_mut(), mock_env(), mock_info("anyone", &[])).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn execute_balance_reconciliation() {
+        let counter_offer = coins(40, "ETH");
+        let collateral = vec![coin(1, "BTC"), coin(10, "OSMO")];
+        let setup = |balance: &[Coin]| {
+            let mut deps = mock_dependencies_with_balance(balance);
+            let msg = InstantiateMsg {
+                counter_offer: counter_offer.clone(),
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &collateral);
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // sufficient, with extra funds on top
+        let mut deps = setup(&[coin(1, "BTC"), coin(12, "OSMO"), coin(3, "ATOM")]);
+        let info = mock_info("creator", &counter_offer);
+        execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+
+        // insufficient in one of the denoms
+        let mut deps = setup(&[coin(1, "BTC"), coin(9, "OSMO")]);
+        let info = mock_info("creator", &counter_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::InsufficientContractBalance { denom } => assert_eq!(denom, "OSMO"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // missing entirely
+        let mut deps = setup(&[coin(10, "OSMO")]);
+        let info = mock_info("creator", &counter_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::InsufficientContractBalance { denom } => assert_eq!(denom, "BTC"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn nft_collateral() {
+        let counter_offer = coins(40, "ETH");
+        let setup = || {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: counter_offer.clone(),
+                expires: 100_000,
+                nft_contract: Some("nft".to_string()),
+                ..Default::default()
+            };
+            let info = mock_info("creator", &[]);
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+        let receive = |sender: &str| {
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: sender.to_string(),
+                token_id: "punk-1".to_string(),
+                msg: Binary::default(),
+            })
+        };
+        let transfer_nft = |recipient: &str| {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "nft".to_string(),
+                msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                    recipient: recipient.to_string(),
+                    token_id: "punk-1".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        };
+
+        let mut deps = setup();
+
+        // cannot execute before the NFT is posted
+        let info = mock_info("creator", &counter_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::NftNotReceived {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // only the configured NFT contract is accepted
+        let info = mock_info("other-nft", &[]);
+        let err = super::execute(deps.as_mut(), mock_env(), info, receive("creator")).unwrap_err();
+        match err {
+            ContractError::UnexpectedNftContract { contract } => assert_eq!(contract, "other-nft"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // only the creator can post it
+        let info = mock_info("nft", &[]);
+        let err = super::execute(deps.as_mut(), mock_env(), info, receive("anyone")).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "receive_nft"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("nft", &[]);
+        super::execute(deps.as_mut(), mock_env(), info, receive("creator")).unwrap();
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(
+            Some((Addr::unchecked("nft"), "punk-1".to_string())),
+            res.nft_collateral
+        );
+
+        let info = mock_info("nft", &[]);
+        let err = super::execute(deps.as_mut(), mock_env(), info, receive("creator")).unwrap_err();
+        match err {
+            ContractError::NftAlreadyReceived {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // execute releases the NFT to the owner
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+        let info = mock_info("owner", &counter_offer);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: counter_offer.clone(),
+            })
+        );
+        assert_eq!(res.messages[1].msg, transfer_nft("owner"));
+
+        // burn returns the NFT to the creator
+        let mut deps = setup();
+        let info = mock_info("nft", &[]);
+        super::execute(deps.as_mut(), mock_env(), info, receive("creator")).unwrap();
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let res = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, transfer_nft("creator"));
+    }
+
+    #[test]
+    fn simulate_execute() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // valid execution with overpayment
+        let res = query_simulate_execute(
+            deps.as_ref(),
+            mock_env(),
+            coins(41, "ETH"),
+            "owner".to_string(),
+        )
+        .unwrap();
+        assert!(res.ok);
+        assert_eq!(res.error, None);
+        assert_eq!(
+            res.would_send,
+            vec![
+                ("creator".to_string(), counter_offer.clone()),
+                ("owner".to_string(), coins(1, "BTC")),
+                ("owner".to_string(), coins(1, "ETH")),
+            ]
+        );
+
+        // underpaid
+        let res = query_simulate_execute(
+            deps.as_ref(),
+            mock_env(),
+            coins(39, "ETH"),
+            "owner".to_string(),
+        )
+        .unwrap();
+        assert!(!res.ok);
+        assert!(res.would_send.is_empty());
+        assert_eq!(
+            res.error.unwrap(),
+            ContractError::CounterOfferMismatch {
+                offer: coins(39, "ETH"),
+                counter_offer: counter_offer.clone(),
+            }
+            .to_string()
+        );
+
+        // expired
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let res =
+            query_simulate_execute(deps.as_ref(), env, counter_offer, "owner".to_string()).unwrap();
+        assert!(!res.ok);
+        assert_eq!(
+            res.error.unwrap(),
+            ContractError::OptionExpired { expired: 100_000 }.to_string()
+        );
+
+        // nothing was changed by simulating
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!("owner", res.owner.as_str());
+    }
+
+    #[test]
+    fn update_counter_offer() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // only the creator can update
+        let info = mock_info("anyone", &[]);
+        let err = execute_update_counter_offer(deps.as_mut(), mock_env(), info, coins(50, "ETH"))
+            .unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "update_counter_offer"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // empty and zero amount counter offers are rejected
+        let info = mock_info("creator", &[]);
+        let err =
+            execute_update_counter_offer(deps.as_mut(), mock_env(), info, vec![]).unwrap_err();
+        match err {
+            ContractError::EmptyCounterOffer {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &[]);
+        let err = execute_update_counter_offer(deps.as_mut(), mock_env(), info, coins(0, "ETH"))
+            .unwrap_err();
+        match err {
+            ContractError::ZeroAmountCoin { denom } => assert_eq!(denom, "ETH"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // creator updates before selling
+        let info = mock_info("creator", &[]);
+        let res = execute_update_counter_offer(deps.as_mut(), mock_env(), info, coins(50, "ETH"))
+            .unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(res.attributes[1], attr("old_counter_offer", "40ETH"));
+        assert_eq!(res.attributes[2], attr("new_counter_offer", "50ETH"));
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(coins(50, "ETH"), res.counter_offer);
+
+        // once transferred, the terms are fixed
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+        let info = mock_info("creator", &[]);
+        let err = execute_update_counter_offer(deps.as_mut(), mock_env(), info, coins(60, "ETH"))
+            .unwrap_err();
+        match err {
+            ContractError::AlreadyTransferred {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(coins(50, "ETH"), res.counter_offer);
+    }
+
+    #[test]
+    fn denom_aliases() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "uatom"), coin(5, "ETH")],
+            expires: 100_000,
+            denom_aliases: vec![("uatom".to_string(), "ibc/ATOM".to_string())],
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // paid in the new denom, the creator receives the new denom
+        let info = mock_info("creator", &[coin(40, "ibc/ATOM"), coin(5, "ETH")]);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                (
+                    "creator".to_string(),
+                    vec![coin(5, "ETH"), coin(40, "ibc/ATOM")]
+                ),
+                ("creator".to_string(), coins(1, "BTC")),
+            ]
+        );
+
+        // SimulateExecute and Quote accept the payment Execute accepts
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "uatom"), coin(5, "ETH")],
+            expires: 100_000,
+            denom_aliases: vec![("uatom".to_string(), "ibc/ATOM".to_string())],
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let pay = vec![coin(40, "ibc/ATOM"), coin(5, "ETH")];
+        let res = query_simulate_execute(deps.as_ref(), mock_env(), pay.clone(), "creator".into())
+            .unwrap();
+        assert!(res.ok, "{:?}", res.error);
+        let res = query_quote(deps.as_ref(), mock_env(), pay).unwrap();
+        assert!(res.sufficient);
+        assert!(res.excess.is_empty());
+
+        // whichever name is paid, exactly the coins received are forwarded and refunded
+        for (counter_offer, paid) in [("uatom", "uatom"), ("ibc/ATOM", "uatom")] {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, counter_offer),
+                expires: 100_000,
+                denom_aliases: vec![("uatom".to_string(), "ibc/ATOM".to_string())],
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let info = mock_info("creator", &coins(50, paid));
+            let res = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+                .unwrap();
+            assert_eq!(
+                bank_sends(&res),
+                vec![
+                    ("creator".to_string(), coins(40, paid)),
+                    ("creator".to_string(), coins(1, "BTC")),
+                    ("creator".to_string(), coins(10, paid)),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn allowed_denoms() {
+        let mut deps = mock_dependencies();
+        let allowed_denoms = vec!["BTC".to_string(), "ETH".to_string()];
+
+        // collateral outside of the allowlist
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            allowed_denoms: allowed_denoms.clone(),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "DOGE"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::DenomNotAllowed { denom } => assert_eq!(denom, "DOGE"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // counter_offer outside of the allowlist
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(5, "DOGE")],
+            expires: 100_000,
+            allowed_denoms: allowed_denoms.clone(),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::DenomNotAllowed { denom } => assert_eq!(denom, "DOGE"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // both sides allowed
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            allowed_denoms,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // the allowlist holds for later counter_offer updates as well
+        let info = mock_info("creator", &[]);
+        let err = execute_update_counter_offer(deps.as_mut(), mock_env(), info, coins(5, "DOGE"))
+            .unwrap_err();
+        match err {
+            ContractError::DenomNotAllowed { denom } => assert_eq!(denom, "DOGE"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn deposit_counter_offer() {
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&[coin(1, "BTC"), coin(40, "ETH")]);
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let info = mock_info("creator", &[]);
+            execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+            deps
+        };
+
+        // nothing attached and nothing escrowed
+        let mut deps = setup();
+        let info = mock_info("owner", &[]);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::NoEscrow {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // only the owner can deposit
+        let info = mock_info("anyone", &coins(40, "ETH"));
+        let err = execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "deposit_counter_offer"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // deposited in two parts, then executed without funds
+        let info = mock_info("owner", &coins(15, "ETH"));
+        execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap();
+        let info = mock_info("owner", &coins(25, "ETH"));
+        execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap();
+        assert_eq!(CONFIG.load(&deps.storage).unwrap().escrow, coins(40, "ETH"));
+        let info = mock_info("owner", &[]);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(40, "ETH")),
+                ("owner".to_string(), coins(1, "BTC")),
+            ]
+        );
+
+        // deposited, then left to expire and claimed back
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap();
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let err = execute_deposit_counter_offer(deps.as_mut(), env.clone(), info).unwrap_err();
+        match err {
+            ContractError::OptionExpired { expired } => assert_eq!(expired, 100_000),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("owner", &[]);
+        let res = execute_claim_expired(deps.as_mut(), env, info).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![("owner".to_string(), coins(40, "ETH"))]
+        );
+
+        // a transfer returns the escrow to the previous owner
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap();
+        let info = mock_info("owner", &[]);
+        let res = execute_transfer(deps.as_mut(), mock_env(), info, "someone".to_string()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![("owner".to_string(), coins(40, "ETH"))]
+        );
+        assert!(CONFIG.load(&deps.storage).unwrap().escrow.is_empty());
+
+        // a rescind returns the escrow to the owner along with the collateral to the creator
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap();
+        let info = mock_info("owner", &[]);
+        execute_propose_rescind(deps.as_mut(), mock_env(), info).unwrap();
+        let info = mock_info("creator", &[]);
+        let res = execute_accept_rescind(deps.as_mut(), mock_env(), info).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(1, "BTC")),
+                ("owner".to_string(), coins(40, "ETH")),
+            ]
+        );
+
+        // executed for the owner, the over-deposit goes back to the owner and not to the agent
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(50, "ETH"));
+        execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap();
+        let info = mock_info("owner", &[]);
+        execute_approve(deps.as_mut(), mock_env(), info, "agent".to_string()).unwrap();
+        let info = mock_info("agent", &coins(5, "ETH"));
+        let res = execute_execute_for(deps.as_mut(), mock_env(), info, true).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(40, "ETH")),
+                ("owner".to_string(), coins(1, "BTC")),
+                ("agent".to_string(), coins(5, "ETH")),
+                ("owner".to_string(), coins(10, "ETH")),
+            ]
+        );
+
+        // nothing can be deposited towards a cash settled option
+        let mut deps = mock_dependencies_with_balance(&coins(100, "USDC"));
+        let msg = InstantiateMsg {
+            expires: 100_000,
+            settlement: Settlement::CashSettled {
+                oracle: Addr::unchecked("oracle"),
+                strike: Uint128::new(1_000),
+            },
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(100, "USDC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let err = execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::NoCounterOffer {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn claim_expired() {
+        let mut deps = mock_dependencies_with_balance(&[coin(1, "BTC"), coin(40, "ETH")]);
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 100_000;
+
+        // nothing escrowed yet
+        let info = mock_info("owner", &[]);
+        let err = execute_claim_expired(deps.as_mut(), env.clone(), info).unwrap_err();
+        match err {
+            ContractError::NothingToClaim {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("owner", &coins(40, "ETH"));
+        execute_deposit_counter_offer(deps.as_mut(), mock_env(), info).unwrap();
+
+        // not claimable before expiry
+        let info = mock_info("owner", &[]);
+        let err = execute_claim_expired(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::OptionNotExpired { expires } => assert_eq!(expires, 100_000),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // only the owner can claim
+        let info = mock_info("creator", &[]);
+        let err = execute_claim_expired(deps.as_mut(), env.clone(), info).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "claim_expired"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("owner", &[]);
+        let res = execute_claim_expired(deps.as_mut(), env.clone(), info).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".into(),
+                amount: coins(40, "ETH"),
+            })
+        );
+
+        // escrow is gone, the collateral still awaits burn
+        let info = mock_info("owner", &[]);
+        let err = execute_claim_expired(deps.as_mut(), env.clone(), info).unwrap_err();
+        match err {
+            ContractError::NothingToClaim {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &[]);
+        let res = execute_burn(deps.as_mut(), env, info).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(1, "BTC"),
+            })
+        );
+    }
+
+    #[test]
+    fn decaying_counter_offer() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        // decay is single denom only
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(100, "ETH"), coin(100, "ATOM")],
+            expires: 100_000,
+            decay_bps_per_block: 100,
+            min_counter_offer: Some(Uint128::new(60)),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::DecayCounterOffer {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // 1% of 100ETH per block, down to 60ETH at most
+        let msg = InstantiateMsg {
+            counter_offer: coins(100, "ETH"),
+            expires: 100_000,
+            decay_bps_per_block: 100,
+            min_counter_offer: Some(Uint128::new(60)),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let start = mock_env().block.height;
+
+        let state = CONFIG.load(&deps.storage).unwrap();
+        let due = |blocks: u64| {
+            let mut env = mock_env();
+            env.block.height = start + blocks;
+            effective_counter_offer(&state, &env).unwrap()
+        };
+        assert_eq!(due(0), coins(100, "ETH"));
+        assert_eq!(due(10), coins(90, "ETH"));
+        assert_eq!(due(25), coins(75, "ETH"));
+        assert_eq!(due(40), coins(60, "ETH"));
+        assert_eq!(due(1_000), coins(60, "ETH"));
+
+        // 25 blocks in, 74ETH is not enough while 75ETH executes
+        let mut env = mock_env();
+        env.block.height = start + 25;
+        let info = mock_info("creator", &coins(74, "ETH"));
+        let err = execute_execute(deps.as_mut(), env.clone(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::CounterOfferMismatch { counter_offer, .. } => {
+                assert_eq!(counter_offer, coins(75, "ETH"))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &coins(75, "ETH"));
+        let res = execute_execute(deps.as_mut(), env, info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(75, "ETH"),
+            })
+        );
+    }
+
+    #[test]
+    fn collar() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        // the floor cannot be above the cap
+        let msg = InstantiateMsg {
+            counter_offer: coins(100, "ETH"),
+            expires: 100_000,
+            floor: Some(coins(91, "ETH")),
+            cap: Some(coins(90, "ETH")),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::InvalidCollar { denom } => assert_eq!(denom, "ETH"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // 1% of 100ETH per block, collared between 70ETH and 90ETH
+        let msg = InstantiateMsg {
+            counter_offer: coins(100, "ETH"),
+            expires: 100_000,
+            decay_bps_per_block: 100,
+            min_counter_offer: Some(Uint128::new(10)),
+            floor: Some(coins(70, "ETH")),
+            cap: Some(coins(90, "ETH")),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let start = mock_env().block.height;
+
+        let state = CONFIG.load(&deps.storage).unwrap();
+        let due = |blocks: u64| {
+            let mut env = mock_env();
+            env.block.height = start + blocks;
+            effective_counter_offer(&state, &env).unwrap()
+        };
+        // capped
+        assert_eq!(due(0), coins(90, "ETH"));
+        assert_eq!(due(5), coins(90, "ETH"));
+        // within the collar
+        assert_eq!(due(20), coins(80, "ETH"));
+        // floored
+        assert_eq!(due(40), coins(70, "ETH"));
+        assert_eq!(due(1_000), coins(70, "ETH"));
+
+        // executing right away pays the cap
+        let info = mock_info("creator", &coins(90, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res)[0],
+            ("creator".to_string(), coins(90, "ETH"))
+        );
+    }
+
+    #[test]
+    fn execute_twice() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &counter_offer);
+        execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+
+        // the option is gone
+        let info = mock_info("creator", &counter_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::OptionNotFound {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap_err();
+        match err {
+            ContractError::OptionNotFound {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn min_collateral() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            min_collateral: coins(10, "BTC"),
+            ..Default::default()
+        };
+
+        // below the minimum
+        let info = mock_info("creator", &coins(9, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        match err {
+            ContractError::CollateralBelowMinimum {
+                denom,
+                required,
+                provided,
+            } => {
+                assert_eq!(denom, "BTC");
+                assert_eq!(required, Uint128::new(10));
+                assert_eq!(provided, Uint128::new(9));
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // a configured denom missing from the collateral
+        let info = mock_info("creator", &coins(100, "ATOM"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        match err {
+            ContractError::CollateralBelowMinimum { provided, .. } => assert!(provided.is_zero()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // minimum met, other denoms have no minimum
+        let info = mock_info("creator", &[coin(10, "BTC"), coin(1, "ATOM")]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn extend_expiry() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            extension_fee: coins(2, "ETH"),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // wrong fee
+        let info = mock_info("owner", &coins(1, "ETH"));
+        let err = execute_extend_expiry(deps.as_mut(), mock_env(), info, 200_000).unwrap_err();
+        match err {
+            ContractError::ExtensionFeeMismatch { paid, fee } => {
+                assert_eq!(paid, coins(1, "ETH"));
+                assert_eq!(fee, coins(2, "ETH"));
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // expiry has to move forward
+        let info = mock_info("owner", &coins(2, "ETH"));
+        let err = execute_extend_expiry(deps.as_mut(), mock_env(), info, 100_000).unwrap_err();
+        match err {
+            ContractError::ExpiryNotIncreased { expires } => assert_eq!(expires, 100_000),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // only the owner can extend
+        let info = mock_info("creator", &coins(2, "ETH"));
+        let err = execute_extend_expiry(deps.as_mut(), mock_env(), info, 200_000).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "extend_expiry"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("owner", &coins(2, "ETH"));
+        let res = execute_extend_expiry(deps.as_mut(), mock_env(), info, 200_000).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(1, res.messages.len());
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(2, "ETH"),
+            })
+        );
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(200_000, res.expires);
+    }
+
+    #[test]
+    fn receipt() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let receipt = |deps: Deps| -> Option<Receipt> {
+            from_binary(&query(deps, mock_env(), QueryMsg::Receipt { id: 0 }).unwrap()).unwrap()
+        };
+        assert_eq!(receipt(deps.as_ref()), None);
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+
+        // the receipt outlives the option
+        let _ = query_config(deps.as_ref(), mock_env()).unwrap_err();
+        assert_eq!(
+            receipt(deps.as_ref()),
+            Some(Receipt {
+                option_id: 0,
+                exerciser: Addr::unchecked("owner"),
+                paid: coins(40, "ETH"),
+                received: coins(1, "BTC"),
+                height: mock_env().block.height,
+            })
+        );
+    }
+
+    #[test]
+    fn last_action() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let last_action = |deps: Deps| -> Option<LastAction> {
+            from_binary(&query(deps, mock_env(), QueryMsg::LastAction {}).unwrap()).unwrap()
+        };
+        assert_eq!(last_action(deps.as_ref()), None);
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        super::execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            last_action(deps.as_ref()),
+            Some(LastAction {
+                action: "transfer".to_string(),
+                height: mock_env().block.height,
+                sender: Addr::unchecked("creator"),
+            })
+        );
+
+        // failed calls leave it untouched
+        let info = mock_info("anyone", &[]);
+        super::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Renounce {}).unwrap_err();
+        assert_eq!(last_action(deps.as_ref()).unwrap().action, "transfer");
+
+        // burn removes the option, but not the last action
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let info = mock_info("anyone", &[]);
+        super::execute(deps.as_mut(), env, info, ExecuteMsg::Burn {}).unwrap();
+        assert!(query_config(deps.as_ref(), mock_env()).is_err());
+        assert_eq!(
+            last_action(deps.as_ref()),
+            Some(LastAction {
+                action: "burn".to_string(),
+                height: 100_000,
+                sender: Addr::unchecked("anyone"),
+            })
+        );
+    }
+
+    #[test]
+    fn receive_as_cw20() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        // a CW20 preference without a router to swap through
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            receive_as: Some("cw20".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::MissingRouter {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            receive_as: Some("cw20".to_string()),
+            router: Some("router".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // the counter_offer goes to the router, which swaps it and pays the creator
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "router".into(),
+                msg: to_binary(&RouterExecuteMsg::Swap {
+                    ask_token: "cw20".into(),
+                    recipient: "creator".into(),
+                })
+                .unwrap(),
+                funds: coins(40, "ETH"),
+            })
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".into(),
+                amount: coins(1, "BTC"),
+            })
+        );
+    }
+
+    #[test]
+    fn admin_handover() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            admin: Some("admin".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // nothing to accept yet
+        let info = mock_info("new_admin", &[]);
+        let err = execute_accept_admin(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::NoAdminProposal {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // only the admin can propose
+        let info = mock_info("creator", &[]);
+        let err = execute_propose_admin(deps.as_mut(), mock_env(), info, "creator".to_string())
+            .unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "propose_admin"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("admin", &[]);
+        execute_propose_admin(deps.as_mut(), mock_env(), info, "new_admin".to_string()).unwrap();
+
+        // only the proposed admin can accept, the old admin stays in charge meanwhile
+        let info = mock_info("anyone", &[]);
+        let err = execute_accept_admin(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "accept_admin"),
+            e => panic!("unexpected error: {}", e),
+        }
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(Some(Addr::unchecked("admin")), res.admin);
+
+        let info = mock_info("new_admin", &[]);
+        let res = execute_accept_admin(deps.as_mut(), mock_env(), info).unwrap();
+        assert_unique_keys(&res.attributes);
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(Some(Addr::unchecked("new_admin")), res.admin);
+
+        // the old admin lost its rights
+        let info = mock_info("admin", &[]);
+        let err = execute_force_expire(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "force_expire"),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("new_admin", &[]);
+        execute_force_expire(deps.as_mut(), mock_env(), info).unwrap();
+    }
+
+    #[test]
+    fn one_block_lifetime() {
+        let env = mock_env();
+        let height = env.block.height;
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: height + 1,
+            ..Default::default()
+        };
+
+        // expiring in the instantiation block is rejected
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            InstantiateMsg {
+                expires: height,
+                ..msg.clone()
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::OptionExpired { expired } => assert_eq!(expired, height),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // one block of lifetime can be executed right away, but not burned
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+        let info = mock_info("creator", &[]);
+        let err = execute_burn(deps.as_mut(), env.clone(), info).unwrap_err();
+        match err {
+            ContractError::OptionNotExpired { expires } => assert_eq!(expires, height + 1),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &coins(40, "ETH"));
+        execute_execute(deps.as_mut(), env.clone(), info, ExerciseParams::default()).unwrap();
+
+        // one block later it can only be burned
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let mut next = env;
+        next.block.height = height + 1;
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let err = execute_execute(deps.as_mut(), next.clone(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::OptionExpired { expired } => assert_eq!(expired, height + 1),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &[]);
+        execute_burn(deps.as_mut(), next, info).unwrap();
+    }
+
+    #[test]
+    fn execute_max_extra() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // 4ETH over the counter_offer, but only 3ETH tolerated
+        let info = mock_info("owner", &coins(44, "ETH"));
+        let err = execute_execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExerciseParams {
+                max_extra: Some(coins(3, "ETH")),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::ExceedsTolerance {
+                denom,
+                extra,
+                max_extra,
+            } => {
+                assert_eq!(denom, "ETH");
+                assert_eq!(extra, Uint128::new(4));
+                assert_eq!(max_extra, Uint128::new(3));
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // within tolerance, the difference is refunded
+        let info = mock_info("owner", &coins(43, "ETH"));
+        let res = execute_execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExerciseParams {
+                max_extra: Some(coins(3, "ETH")),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[2].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".into(),
+                amount: coins(3, "ETH"),
+            })
+        );
+    }
+
+    #[test]
+    fn storage_errors() {
+        let err = ContractError::storage_load(StdError::not_found("State"));
+        match &err {
+            ContractError::StorageLoad { source } => {
+                assert_eq!(*source, StdError::not_found("State"))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(
+            err.to_string(),
+            "failed to load from storage: State not found"
+        );
+
+        let err = ContractError::storage_save(StdError::generic_err("disk full"));
+        match &err {
+            ContractError::StorageSave { source } => {
+                assert_eq!(*source, StdError::generic_err("disk full"))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(
+            err.to_string(),
+            "failed to save to storage: Generic error: disk full"
+        );
+    }
+
+    #[test]
+    fn pay_in_one_denom() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        // 40ETH + 100ATOM, where 1ATOM is worth 0.25ETH - 65ETH in total
+        let counter_offer = vec![coin(40, "ETH"), coin(100, "ATOM")];
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            denom_ratios: vec![("ATOM".to_string(), "ETH".to_string(), Decimal::percent(25))],
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // no ratio into ATOM
+        let info = mock_info("owner", &coins(1_000, "ATOM"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::CounterOfferMismatch {
+                counter_offer: due, ..
+            } => {
+                assert_eq!(due, normalize_coins(counter_offer.clone()))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // short of the converted value
+        let info = mock_info("owner", &coins(64, "ETH"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::CounterOfferMismatch { counter_offer, .. } => {
+                assert_eq!(counter_offer, coins(65, "ETH"))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the full value in ETH, the creator receives it in ETH
+        let info = mock_info("owner", &coins(65, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(65, "ETH"),
+            })
+        );
+    }
+
+    #[test]
+    fn ratio_rounding() {
+        let third = Decimal::from_ratio(1u128, 3u128);
+        assert_eq!(mul_ratio(Uint128::new(10), third).unwrap(), Uint128::new(3));
+        assert_eq!(
+            mul_ratio_ceil(Uint128::new(10), third).unwrap(),
+            Uint128::new(4)
+        );
+        assert_eq!(
+            mul_ratio_ceil(Uint128::new(9), third).unwrap(),
+            Uint128::new(3)
+        );
+    }
+
+    #[test]
+    fn burn_reward() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "BTC"));
+
+        // the reward must be covered by the collateral
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            burn_reward: coins(1, "ETH"),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(100, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::BurnRewardExceedsCollateral {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            burn_reward: coins(2, "BTC"),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(100, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // a third party keeper burns, the creator gets the rest
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let info = mock_info("keeper", &[]);
+        let res = execute_burn(deps.as_mut(), env, info).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(98, "BTC"),
+            })
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "keeper".into(),
+                amount: coins(2, "BTC"),
+            })
+        );
+
+        // without a reward the keeper gets nothing
+        let mut deps = mock_dependencies_with_balance(&coins(100, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(100, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let info = mock_info("keeper", &[]);
+        let res = execute_burn(deps.as_mut(), env, info).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(100, "BTC"),
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_stored_owner() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // corrupt the stored owner, bypassing validation
+        CONFIG
+            .update(deps.as_mut().storage, |mut state| -> StdResult<_> {
+                state.owner = Addr::unchecked("NOT_NORMALIZED");
+                Ok(state)
+            })
+            .unwrap();
+
+        let info = mock_info("NOT_NORMALIZED", &counter_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::InvalidStoredAddress { address } => {
+                assert_eq!(address, "NOT_NORMALIZED")
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn label_and_memo() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            label: Some("desk A / BTC call".to_string()),
+            memo: Some("x".repeat(MAX_LABEL_LENGTH + 1)),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        match err {
+            ContractError::LabelTooLong { field, max } => {
+                assert_eq!(field, "memo");
+                assert_eq!(max, MAX_LABEL_LENGTH);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let msg = InstantiateMsg {
+            memo: Some("x".repeat(MAX_LABEL_LENGTH)),
+            ..msg
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(res.label.as_deref(), Some("desk A / BTC call"));
+        assert_eq!(res.memo.unwrap().len(), MAX_LABEL_LENGTH);
+    }
+
+    #[test]
+    fn execute_for() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // not approved yet
+        let info = mock_info("agent", &counter_offer);
+        let err = execute_execute_for(deps.as_mut(), mock_env(), info, true).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "execute"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let info = mock_info("owner", &[]);
+        execute_approve(deps.as_mut(), mock_env(), info, "agent".to_string()).unwrap();
+
+        // approval alone does not allow a plain execute
+        let info = mock_info("agent", &counter_offer);
+        let err = execute_execute_for(deps.as_mut(), mock_env(), info, false).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "execute"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the agent pays, the owner gets the collateral
+        let info = mock_info("agent", &counter_offer);
+        let res = execute_execute_for(deps.as_mut(), mock_env(), info, true).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: counter_offer,
+            })
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".into(),
+                amount: coins(1, "BTC"),
+            })
+        );
+    }
+
+    #[test]
+    fn reject_degenerate() {
+        let mut deps = mock_dependencies();
+
+        // the same coins both ways, in a different order
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(5, "ATOM"), coin(1, "BTC")],
+            expires: 100_000,
+            reject_degenerate: true,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(5, "ATOM")]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        match err {
+            ContractError::DegenerateOption {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // allowed without the flag
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(5, "ATOM")]);
+        let degenerate = InstantiateMsg {
+            reject_degenerate: false,
+            ..msg.clone()
+        };
+        instantiate(deps.as_mut(), mock_env(), info, degenerate).unwrap();
+
+        // different amounts are a real option
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(4, "ATOM")]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn execute_deadline() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let height = mock_env().block.height;
+
+        // included a block too late
+        let mut env = mock_env();
+        env.block.height = height + 11;
+        let info = mock_info("creator", &counter_offer);
+        let err = execute_execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExerciseParams {
+                deadline: Some(height + 10),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::DeadlineExceeded { deadline } => assert_eq!(deadline, height + 10),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the deadline block itself is fine
+        env.block.height = height + 10;
+        let info = mock_info("creator", &counter_offer);
+        execute_execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExerciseParams {
+                deadline: Some(height + 10),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn split_sends() {
+        let mut deps = mock_dependencies_with_balance(&[coin(2, "OSMO"), coin(1, "BTC")]);
+
+        let counter_offer = vec![coin(40, "ETH"), coin(5, "ATOM")];
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            split_sends: true,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(2, "OSMO"), coin(1, "BTC")]);
+        instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+        // one send per denom, sorted by denom within each payment
+        let send = |to: &str, amount: Coin| {
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: to.into(),
+                amount: vec![amount],
+            })
+        };
+        let info = mock_info("creator", &counter_offer);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        let msgs: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                send("creator", coin(5, "ATOM")),
+                send("creator", coin(40, "ETH")),
+                send("creator", coin(1, "BTC")),
+                send("creator", coin(2, "OSMO")),
+            ]
+        );
+
+        // burn splits as well
+        let info = mock_info("creator", &[coin(2, "OSMO"), coin(1, "BTC")]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let info = mock_info("creator", &[]);
+        let res = execute_burn(deps.as_mut(), env, info).unwrap();
+        let msgs: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                send("creator", coin(1, "BTC")),
+                send("creator", coin(2, "OSMO"))
+            ]
+        );
+    }
+
+    fn mock_compliance(blocked: &'static str) -> impl Fn(&WasmQuery) -> QuerierResult {
+        move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "compliance" => {
+                let ComplianceQueryMsg::IsBlocked { address } = from_binary(msg).unwrap();
+                let res = IsBlockedResponse {
+                    blocked: address == blocked,
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            q => panic!("unexpected query: {:?}", q),
+        }
+    }
+
+    #[test]
+    fn compliance() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            compliance_contract: Some("compliance".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // a blocked creator prevents execution and burn
+        deps.querier.update_wasm(mock_compliance("creator"));
+        let info = mock_info("owner", &counter_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::AddressBlocked { address } => assert_eq!(address, "creator"),
+            e => panic!("unexpected error: {}", e),
+        }
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let info = mock_info("owner", &[]);
+        let err = execute_burn(deps.as_mut(), env, info).unwrap_err();
+        match err {
+            ContractError::AddressBlocked { address } => assert_eq!(address, "creator"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // with a clean creator it goes through
+        deps.querier.update_wasm(mock_compliance("someone else"));
+        let info = mock_info("owner", &counter_offer);
+        execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+    }
+
+    #[test]
+    fn exercise_hook() {
+        let counter_offer = coins(40, "ETH");
+        let setup = |exercise_hook: Option<String>| {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: counter_offer.clone(),
+                expires: 100_000,
+                exercise_hook,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // no hook, no submessage
+        let mut deps = setup(None);
+        let info = mock_info("creator", &counter_offer);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert!(res
+            .messages
+            .iter()
+            .all(|sub| sub.reply_on == ReplyOn::Never));
+
+        // the hook is notified last, replying always
+        let mut deps = setup(Some("hook".to_string()));
+        let info = mock_info("creator", &counter_offer);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[2],
+            SubMsg::reply_always(
+                WasmMsg::Execute {
+                    contract_addr: "hook".to_string(),
+                    msg: to_binary(&HookExecuteMsg::OptionExecuted {
+                        creator: "creator".to_string(),
+                        owner: "creator".to_string(),
+                        amount: counter_offer.clone(),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                },
+                EXERCISE_HOOK_REPLY_ID
+            )
+        );
+
+        // a failing hook fails the exercise
+        let reply_msg = Reply {
+            id: EXERCISE_HOOK_REPLY_ID,
+            result: SubMsgResult::Err("hook out of gas".to_string()),
+        };
+        let err = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+        match err {
+            ContractError::HookFailed { error } => assert_eq!(error, "hook out of gas"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn reentrancy() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            exercise_hook: Some("hook".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // exercising locks execution until the hook replies
+        let info = mock_info("creator", &coins(40, "ETH"));
+        execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(EXECUTING.may_load(&deps.storage).unwrap(), Some(true));
+
+        // the hook calling back into execute before its reply is rejected
+        let info = mock_info("hook", &coins(40, "ETH"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::Reentrancy {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the successful reply releases the lock
+        let reply_msg = Reply {
+            id: EXERCISE_HOOK_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+        assert_eq!(EXECUTING.may_load(&deps.storage).unwrap(), None);
+    }
+
+    #[test]
+    fn min_lifetime() {
+        let mut deps = mock_dependencies();
+        let height = mock_env().block.height;
+
+        // exactly the minimum lifetime is accepted
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: height + 10,
+            min_lifetime_blocks: Some(10),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // one block below is rejected
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: height + 9,
+            min_lifetime_blocks: Some(10),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::LifetimeTooShort { min } => assert_eq!(min, 10),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // by default a single block is enough
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: height + 1,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn ibc_denoms() {
+        let valid = format!(
+            "ibc/{}",
+            "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+        let malformed = "ibc/27394fb092d2eccd".to_string();
+        let mut deps = mock_dependencies();
+
+        // a well formed IBC denom is accepted under the flag
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, &valid),
+            expires: 100_000,
+            validate_ibc_denoms: true,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // a malformed one is rejected, in the counter_offer as well as in the collateral
+        for (collateral, counter_offer) in [
+            (coins(1, "BTC"), coins(40, &malformed)),
+            (coins(1, &malformed), coins(40, "ETH")),
+        ] {
+            let msg = InstantiateMsg {
+                counter_offer,
+                expires: 100_000,
+                validate_ibc_denoms: true,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &collateral);
+            let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+            match err {
+                ContractError::InvalidIbcDenom { denom } => assert_eq!(denom, malformed),
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+
+        // and accepted without the flag
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, &malformed),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn quote() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let quote = |pay: Vec<Coin>| query_quote(deps.as_ref(), mock_env(), pay).unwrap();
+
+        // underpaid, with ATOM missing entirely
+        let res = quote(coins(30, "ETH"));
+        assert!(!res.sufficient);
+        assert_eq!(res.shortfall, vec![coin(5, "ATOM"), coin(10, "ETH")]);
+        assert!(res.excess.is_empty());
+
+        // exact, in any order
+        let res = quote(vec![coin(5, "ATOM"), coin(40, "ETH")]);
+        assert!(res.sufficient);
+        assert!(res.shortfall.is_empty());
+        assert!(res.excess.is_empty());
+
+        // overpaid, including an unrelated denom
+        let res = quote(vec![coin(42, "ETH"), coin(5, "ATOM"), coin(1, "OSMO")]);
+        assert!(res.sufficient);
+        assert!(res.shortfall.is_empty());
+        assert_eq!(res.excess, vec![coin(2, "ETH"), coin(1, "OSMO")]);
+    }
+
+    #[test]
+    fn normalized_coins() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info(
+            "creator",
+            &[coin(2, "OSMO"), coin(1, "BTC"), coin(3, "OSMO")],
+        );
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // stored sorted by denom, duplicates summed
+        let state = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(state.collateral, vec![coin(1, "BTC"), coin(5, "OSMO")]);
+        assert_eq!(state.counter_offer, vec![coin(5, "ATOM"), coin(40, "ETH")]);
+
+        // updates are normalized as well
+        let info = mock_info("creator", &[]);
+        execute_update_counter_offer(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            vec![coin(1, "OSMO"), coin(2, "ATOM")],
+        )
+        .unwrap();
+        let state = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(state.counter_offer, vec![coin(2, "ATOM"), coin(1, "OSMO")]);
+    }
+
+    #[test]
+    fn withdraw() {
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                early_withdraw_window: 100,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+        let at = |height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            env
+        };
+
+        // outside the window
+        let mut deps = setup();
+        let info = mock_info("creator", &[]);
+        let err = execute_withdraw(deps.as_mut(), at(99_899), info).unwrap_err();
+        match err {
+            ContractError::WithdrawWindowNotOpen { opens } => assert_eq!(opens, 99_900),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // inside the window the collateral goes back to the creator
+        let info = mock_info("creator", &[]);
+        let res = execute_withdraw(deps.as_mut(), at(99_900), info).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![("creator".to_string(), coins(1, "BTC"))]
+        );
+        let _ = query_config(deps.as_ref(), mock_env()).unwrap_err();
+
+        // not once the option was transferred
+        let mut deps = setup();
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+        let info = mock_info("creator", &[]);
+        let err = execute_withdraw(deps.as_mut(), at(99_950), info).unwrap_err();
+        match err {
+            ContractError::AlreadyTransferred {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn cw20_counter_offer() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            cw20_counter_offer: Some(("cw20".to_string(), Uint128::new(500))),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // the native part is sent as usual, the CW20 part pulled from the owner's allowance
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(40, "ETH"),
+            })
+        );
+        assert_eq!(
+            res.messages[2],
+            SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: "cw20".to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: "owner".to_string(),
+                        recipient: "creator".to_string(),
+                        amount: Uint128::new(500),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                },
+                CW20_TRANSFER_REPLY_ID
+            )
+        );
+
+        // a missing allowance fails the exercise
+        let reply_msg = Reply {
+            id: CW20_TRANSFER_REPLY_ID,
+            result: SubMsgResult::Err("no allowance for this account".to_string()),
+        };
+        let err = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+        match err {
+            ContractError::Cw20TransferFailed { error } => {
+                assert_eq!(error, "no allowance for this account")
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn holder_rebate() {
+        let counter_offer = coins(45, "ETH");
+        let setup = |holder_rebate_bps: u16| {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: counter_offer.clone(),
+                expires: 100_000,
+                holder_rebate_bps,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg)?;
+            let info = mock_info("creator", &[]);
+            execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string())?;
+            Ok::<_, ContractError>(deps)
+        };
+
+        // no rebate
+        let mut deps = setup(0).unwrap();
+        let info = mock_info("owner", &counter_offer);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(45, "ETH")),
+                ("owner".to_string(), coins(1, "BTC")),
+            ]
+        );
+
+        // 10% back to the holder, 4.5ETH rounded down in favour of the creator
+        let mut deps = setup(1_000).unwrap();
+        let info = mock_info("owner", &counter_offer);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(41, "ETH")),
+                ("owner".to_string(), coins(1, "BTC")),
+                ("owner".to_string(), coins(4, "ETH")),
+            ]
+        );
+
+        // the creator has to keep something
+        match setup(10_000).err().unwrap() {
+            ContractError::RebateTooHigh { bps } => assert_eq!(bps, 10_000),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn referral() {
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                referral_bps: 1_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let info = mock_info("creator", &[]);
+            execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+            deps
+        };
+
+        // without a referrer the creator gets it all
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(40, "ETH")),
+                ("owner".to_string(), coins(1, "BTC")),
+            ]
+        );
+
+        // 10% goes to the referrer
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let params = ExerciseParams {
+            referrer: Some(Addr::unchecked("referrer")),
+            ..Default::default()
+        };
+        let res = execute_execute(deps.as_mut(), mock_env(), info, params).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(36, "ETH")),
+                ("owner".to_string(), coins(1, "BTC")),
+                ("referrer".to_string(), coins(4, "ETH")),
+            ]
+        );
+
+        // the referrer has to be a valid address
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let msg = ExecuteMsg::Execute {
+            max_extra: None,
+            deadline: None,
+            expected_counter_offer: None,
+            min_collateral_out: None,
+            referrer: Some("".to_string()),
+        };
+        let err = super::execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::InvalidAddress { input, .. } => assert_eq!(input, ""),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // neither the sender nor the owner can refer themselves into a discount
+        let mut deps = setup();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let params = ExerciseParams {
+            referrer: Some(Addr::unchecked("owner")),
+            ..Default::default()
+        };
+        let err = execute_execute(deps.as_mut(), mock_env(), info, params).unwrap_err();
+        match err {
+            ContractError::SelfReferral { referrer } => assert_eq!(referrer, "owner"),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("owner", &[]);
+        execute_approve(deps.as_mut(), mock_env(), info, "agent".to_string()).unwrap();
+        let info = mock_info("agent", &coins(40, "ETH"));
+        let params = ExerciseParams {
+            delegated: true,
+            referrer: Some(Addr::unchecked("agent")),
+            ..Default::default()
+        };
+        let err = execute_execute(deps.as_mut(), mock_env(), info, params).unwrap_err();
+        match err {
+            ContractError::SelfReferral { referrer } => assert_eq!(referrer, "agent"),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("agent", &coins(40, "ETH"));
+        let params = ExerciseParams {
+            delegated: true,
+            referrer: Some(Addr::unchecked("owner")),
+            ..Default::default()
+        };
+        let err = execute_execute(deps.as_mut(), mock_env(), info, params).unwrap_err();
+        match err {
+            ContractError::SelfReferral { referrer } => assert_eq!(referrer, "owner"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn allow_creator_exercise() {
+        let setup = |allow_creator_exercise: Option<bool>| {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                allow_creator_exercise,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // barred, even though the creator still owns the option
+        let mut deps = setup(Some(false));
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::CreatorCannotExercise {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // allowed explicitly and by default
+        for allow in [Some(true), None] {
+            let mut deps = setup(allow);
+            let info = mock_info("creator", &coins(40, "ETH"));
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        }
+    }
+
+    #[test]
+    fn pause_option() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
 
-        let amount = coins(40, "ETH");
-        let collateral = coins(1, "BTC");
-        let expires = 100_000;
         let msg = InstantiateMsg {
-            counter_offer: amount.clone(),
-            expires,
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
         };
-        let info = mock_info("creator", &collateral);
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
 
-        // we can just call .unwrap() to assert this was a success
-        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        // only the creator may pause
+        let info = mock_info("anyone", &[]);
+        let err = execute_pause_option(deps.as_mut(), mock_env(), info, true).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "pause_option"),
+            e => panic!("unexpected error: {}", e),
+        }
 
-        // set new owner
+        // a paused option cannot be executed
         let info = mock_info("creator", &[]);
-        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+        execute_pause_option(deps.as_mut(), mock_env(), info, true).unwrap();
+        assert!(CONFIG.load(&deps.storage).unwrap().paused);
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::OptionPaused {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
 
-        // random cannot execute
-        let info = mock_info("creator", &amount);
-        let err = execute_execute(deps.as_mut(), mock_env(), info).unwrap_err();
+        // unpausing re-enables execute
+        let info = mock_info("creator", &[]);
+        execute_pause_option(deps.as_mut(), mock_env(), info, false).unwrap();
+        let info = mock_info("creator", &coins(40, "ETH"));
+        execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+
+        // burn after expiry still works while paused
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+        let info = mock_info("creator", &[]);
+        execute_pause_option(deps.as_mut(), mock_env(), info, true).unwrap();
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+
+        // not once transferred
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+        let info = mock_info("creator", &[]);
+        let err = execute_pause_option(deps.as_mut(), mock_env(), info, true).unwrap_err();
         match err {
-            ContractError::Unauthorized {} => {}
+            ContractError::AlreadyTransferred {} => {}
             e => panic!("unexpected error: {}", e),
         }
+    }
+
+    #[test]
+    fn exercise_analytics_attributes() {
+        let mut deps = mock_dependencies_with_balance(&[coin(1, "BTC"), coin(2, "OSMO")]);
+
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(2, "OSMO")]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // expired cannot execute
-        let info = mock_info("owner", &amount);
         let mut env = mock_env();
-        env.block.height = 200_000;
-        let err = execute_execute(deps.as_mut(), env, info).unwrap_err();
+        env.block.height = 12_500;
+        let info = mock_info("creator", &[coin(40, "ETH"), coin(6, "ATOM")]);
+        let res = execute_execute(deps.as_mut(), env, info, ExerciseParams::default()).unwrap();
+        assert_unique_keys(&res.attributes);
+        assert_eq!(
+            res.attributes[1..],
+            [
+                attr("collateral_value", "1BTC,2OSMO"),
+                attr("counter_offer_value", "5ATOM,40ETH"),
+                attr("exercised_at_height", "12500"),
+            ]
+        );
+    }
+
+    #[test]
+    fn cancel_approval() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // nothing to cancel yet
+        let info = mock_info("creator", &[]);
+        let err = execute_cancel_approval(deps.as_mut(), mock_env(), info).unwrap_err();
         match err {
-            ContractError::OptionExpired { expired } => assert_eq!(expired, expires),
+            ContractError::NoApproval {} => {}
             e => panic!("unexpected error: {}", e),
         }
 
-        // bad counter_offer cannot execute
-        let msg_offer = coins(39, "ETH");
-        let info = mock_info("owner", &msg_offer);
-        let err = execute_execute(deps.as_mut(), mock_env(), info).unwrap_err();
+        // approve then cancel
+        let info = mock_info("creator", &[]);
+        execute_approve(deps.as_mut(), mock_env(), info, "market".to_string()).unwrap();
+        let info = mock_info("creator", &[]);
+        let res = execute_cancel_approval(deps.as_mut(), mock_env(), info).unwrap();
+        assert_eq!(res.attributes[0], attr("action", "cancel_approval"));
+        assert_eq!(None, CONFIG.load(&deps.storage).unwrap().approved);
+
+        // the former spender can no longer transfer
+        let info = mock_info("market", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), mock_env(), info, "buyer".to_string()).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "transfer"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn expected_counter_offer() {
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // matching expectation, in any order
+        let mut deps = setup();
+        let expected = vec![coin(40, "ETH"), coin(5, "ATOM")];
+        let info = mock_info("creator", &expected);
+        execute_execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExerciseParams {
+                expected_counter_offer: Some(expected),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // stale expectation after an update
+        let mut deps = setup();
+        let info = mock_info("creator", &[]);
+        execute_update_counter_offer(deps.as_mut(), mock_env(), info, coins(50, "ETH")).unwrap();
+        let expected = vec![coin(40, "ETH"), coin(5, "ATOM")];
+        let info = mock_info("creator", &coins(50, "ETH"));
+        let err = execute_execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExerciseParams {
+                expected_counter_offer: Some(expected),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::StateChanged { counter_offer } => {
+                assert_eq!(counter_offer, coins(50, "ETH"))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn min_collateral_out() {
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&[coin(2, "BTC"), coin(10, "OSMO")]);
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &[coin(2, "BTC"), coin(10, "OSMO")]);
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // the collateral covers the minimum
+        let mut deps = setup();
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let min = vec![coin(2, "BTC"), coin(5, "OSMO")];
+        execute_execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExerciseParams {
+                min_collateral_out: Some(min),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // more BTC expected than held
+        let mut deps = setup();
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let min = vec![coin(3, "BTC")];
+        let err = execute_execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExerciseParams {
+                min_collateral_out: Some(min),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::CollateralBelowExpected {
+                denom,
+                expected,
+                held,
+            } => {
+                assert_eq!(denom, "BTC");
+                assert_eq!(expected, Uint128::new(3));
+                assert_eq!(held, Uint128::new(2));
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn rounding_tolerance() {
+        let counter_offer = coins(1_000_000, "uusdc");
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: counter_offer.clone(),
+                expires: 100_000,
+                tolerance: coins(1, "uusdc"),
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // off by one either way is forwarded exactly as sent
+        for paid in [999_999, 1_000_001] {
+            let mut deps = setup();
+            let info = mock_info("creator", &coins(paid, "uusdc"));
+            let res = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+                .unwrap();
+            assert_eq!(
+                bank_sends(&res),
+                vec![
+                    ("creator".to_string(), coins(paid, "uusdc")),
+                    ("creator".to_string(), coins(1, "BTC")),
+                ]
+            );
+        }
+
+        // off by two short fails
+        let mut deps = setup();
+        let info = mock_info("creator", &coins(999_998, "uusdc"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
         match err {
             ContractError::CounterOfferMismatch {
-                offer,
-                counter_offer,
+                counter_offer: due, ..
             } => {
-                assert_eq!(msg_offer, offer);
-                assert_eq!(amount, counter_offer);
+                assert_eq!(due, counter_offer)
             }
             e => panic!("unexpected error: {}", e),
         }
+    }
 
-        // proper execution
-        let info = mock_info("owner", &amount);
-        let res = execute_execute(deps.as_mut(), mock_env(), info).unwrap();
-        assert_eq!(res.messages.len(), 2);
+    #[test]
+    fn nonzero_sends() {
         assert_eq!(
-            res.messages[0].msg,
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: "creator".into(),
-                amount,
-            })
+            filter_nonzero(vec![coin(0, "ETH"), coin(1, "BTC"), coin(0, "ATOM")]),
+            coins(1, "BTC")
         );
         assert_eq!(
-            res.messages[1].msg,
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: "owner".into(),
-                amount: collateral,
-            })
+            bank_send(&Addr::unchecked("someone"), coins(0, "ETH")),
+            None
         );
 
-        // check deleted
-        let _ = query_config(deps.as_ref()).unwrap_err();
+        // a rebate rounding down to zero produces no rebate message
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: coins(9, "ETH"),
+            expires: 100_000,
+            holder_rebate_bps: 1_000,
+            burn_reward: coins(1, "BTC"),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+        let info = mock_info("creator", &coins(9, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("creator".to_string(), coins(9, "ETH")),
+                ("creator".to_string(), coins(1, "BTC")),
+            ]
+        );
+
+        // the whole collateral paid out as burn reward leaves no collateral message
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let res = execute_burn(deps.as_mut(), env, mock_info("keeper", &[])).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![("keeper".to_string(), coins(1, "BTC"))]
+        );
+
+        // an empty counter_offer pays the creator nothing rather than an empty send
+        let msg = InstantiateMsg {
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![("creator".to_string(), coins(1, "BTC"))]
+        );
     }
 
     #[test]
-    fn burn() {
-        let mut deps = mock_dependencies();
+    fn settlement_delay() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
 
-        let counter_offer = coins(40, "ETH");
-        let collateral = coins(1, "BTC");
-        let msg_expires = 100_000;
         let msg = InstantiateMsg {
-            counter_offer: counter_offer.clone(),
-            expires: msg_expires,
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            settlement_delay_blocks: 10,
+            ..Default::default()
         };
-        let info = mock_info("creator", &collateral);
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
 
-        // we can just call .unwrap() to assert this was a success
-        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
 
-        // set new owner
+        // NFT collateral cannot be held back
+        let nft_msg = InstantiateMsg {
+            nft_contract: Some("nft".to_string()),
+            ..msg
+        };
+        let mut nft_deps = mock_dependencies();
         let info = mock_info("creator", &[]);
-        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+        let err = instantiate(nft_deps.as_mut(), mock_env(), info, nft_msg).unwrap_err();
+        match err {
+            ContractError::DelayedNftCollateral {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
 
-        // non-expired cannot execute
-        let info = mock_info("anyone", &[]);
-        let err = execute_burn(deps.as_mut(), mock_env(), info).unwrap_err();
+        // nor can a cash settled payout
+        let cash_msg = InstantiateMsg {
+            counter_offer: vec![],
+            expires: 100_000,
+            settlement: Settlement::CashSettled {
+                oracle: Addr::unchecked("oracle"),
+                strike: Uint128::new(10),
+            },
+            settlement_delay_blocks: 10,
+            ..Default::default()
+        };
+        let mut cash_deps = mock_dependencies();
+        let info = mock_info("creator", &coins(100, "BTC"));
+        let err = instantiate(cash_deps.as_mut(), mock_env(), info, cash_msg).unwrap_err();
         match err {
-            ContractError::OptionNotExpired { expires } => assert_eq!(expires, msg_expires),
+            ContractError::DelayedCashSettlement {} => {}
             e => panic!("unexpected error: {}", e),
         }
 
-        // with funds cannot execute
-        let info = mock_info("anyone", &counter_offer);
-        let mut env = mock_env();
-        env.block.height = 200_000;
-        let err = execute_burn(deps.as_mut(), env, info).unwrap_err();
+        // only the counter_offer is paid right away
+        let height = mock_env().block.height;
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![("creator".to_string(), coins(40, "ETH"))]
+        );
+        assert_eq!(
+            PENDING_RELEASE.load(&deps.storage).unwrap(),
+            PendingRelease {
+                recipient: Addr::unchecked("owner"),
+                amount: coins(1, "BTC"),
+                release_height: height + 10,
+            }
+        );
+
+        let at = |height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            env
+        };
+
+        // too early
+        let info = mock_info("owner", &[]);
+        let err = execute_claim_release(deps.as_mut(), at(height + 9), info).unwrap_err();
         match err {
-            ContractError::FundsSentWithBurn {} => {}
+            ContractError::ReleaseNotReady { release_height } => {
+                assert_eq!(release_height, height + 10)
+            }
             e => panic!("unexpected error: {}", e),
         }
 
-        // expired returns funds
-        let info = mock_info("anyone", &[]);
+        // only the recipient claims
+        let info = mock_info("creator", &[]);
+        let err = execute_claim_release(deps.as_mut(), at(height + 10), info).unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "claim_release"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // released once the height is reached, and only once
+        let info = mock_info("owner", &[]);
+        let res = execute_claim_release(deps.as_mut(), at(height + 10), info).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![("owner".to_string(), coins(1, "BTC"))]
+        );
+        let info = mock_info("owner", &[]);
+        let err = execute_claim_release(deps.as_mut(), at(height + 10), info).unwrap_err();
+        match err {
+            ContractError::NoPendingRelease {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn payout_splits() {
+        let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+
+        // shares have to add up to the whole counter_offer
+        let msg = InstantiateMsg {
+            counter_offer: coins(101, "ETH"),
+            expires: 100_000,
+            payout_splits: vec![("alice".to_string(), 7_000), ("bob".to_string(), 2_000)],
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::InvalidSplits {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(101, "ETH"),
+            expires: 100_000,
+            payout_splits: vec![("alice".to_string(), 7_000), ("bob".to_string(), 3_000)],
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // 70/30 of 101ETH, the rounding dust going to the first payee
+        let info = mock_info("creator", &coins(101, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(
+            bank_sends(&res),
+            vec![
+                ("alice".to_string(), coins(71, "ETH")),
+                ("bob".to_string(), coins(30, "ETH")),
+                ("creator".to_string(), coins(1, "BTC")),
+            ]
+        );
+    }
+
+    #[test]
+    fn co_owners() {
+        let setup = || {
+            let mut deps = mock_dependencies_with_balance(&coins(101, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                owners: vec![("alice".to_string(), 6_000), ("bob".to_string(), 4_000)],
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(101, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // shares have to add up to the whole collateral
+        let mut deps = mock_dependencies_with_balance(&coins(101, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            owners: vec![("alice".to_string(), 6_000), ("bob".to_string(), 3_000)],
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(101, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::InvalidOwners {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the creator cannot co-own their own option
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            owners: vec![("creator".to_string(), 5_000), ("bob".to_string(), 5_000)],
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(101, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::InvalidOwners {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // either co-owner can execute, 60/40 of 101BTC with the dust going to the first
+        for sender in ["alice", "bob"] {
+            let mut deps = setup();
+            let info = mock_info(sender, &coins(40, "ETH"));
+            let res = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+                .unwrap();
+            assert_eq!(
+                bank_sends(&res),
+                vec![
+                    ("creator".to_string(), coins(40, "ETH")),
+                    ("alice".to_string(), coins(61, "BTC")),
+                    ("bob".to_string(), coins(40, "BTC")),
+                ]
+            );
+        }
+
+        // outsiders cannot execute, and a co-owned option cannot be transferred
+        let mut deps = setup();
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::Unauthorized { action } => assert_eq!(action, "execute"),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("alice", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), mock_env(), info, "carol".to_string()).unwrap_err();
+        match err {
+            ContractError::CoOwned {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // burned to the owner, the collateral is split between the co-owners as well
+        let mut deps = mock_dependencies_with_balance(&coins(101, "BTC"));
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            owners: vec![("alice".to_string(), 6_000), ("bob".to_string(), 4_000)],
+            burn_beneficiary: BurnBeneficiary::Owner,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(101, "BTC"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
         let mut env = mock_env();
-        env.block.height = 200_000;
-        let res = execute_burn(deps.as_mut(), env, info).unwrap();
-        assert_eq!(res.messages.len(), 1);
+        env.block.height = 100_000;
+        let res = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
         assert_eq!(
-            res.messages[0].msg,
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: "creator".into(),
-                amount: collateral,
-            })
+            bank_sends(&res),
+            vec![
+                ("alice".to_string(), coins(61, "BTC")),
+                ("bob".to_string(), coins(40, "BTC")),
+            ]
         );
+    }
 
-        // check deleted
-        let _ = query_config(deps.as_ref()).unwrap_err();
+    #[test]
+    fn max_submsgs() {
+        let setup = |max_submsgs: u32| {
+            let mut deps = mock_dependencies_with_balance(&coins(1, "BTC"));
+            let msg = InstantiateMsg {
+                counter_offer: coins(100, "ETH"),
+                expires: 100_000,
+                payout_splits: vec![
+                    ("alice".to_string(), 5_000),
+                    ("bob".to_string(), 3_000),
+                    ("carol".to_string(), 2_000),
+                ],
+                max_submsgs: Some(max_submsgs),
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // three payees and the collateral are four messages
+        let mut deps = setup(3);
+        let info = mock_info("creator", &coins(100, "ETH"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default())
+            .unwrap_err();
+        match err {
+            ContractError::TooManyMessages { count, max } => {
+                assert_eq!(count, 4);
+                assert_eq!(max, 3);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let mut deps = setup(4);
+        let info = mock_info("creator", &coins(100, "ETH"));
+        let res =
+            execute_execute(deps.as_mut(), mock_env(), info, ExerciseParams::default()).unwrap();
+        assert_eq!(res.messages.len(), 4);
     }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Coin, StdError};
+use cosmwasm_std::{Coin, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,14 +6,23 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("failed to load from storage: {source}")]
+    StorageLoad { source: StdError },
+
+    #[error("failed to save to storage: {source}")]
+    StorageSave { source: StdError },
+
+    #[error("option not found, it was already executed, burned or rescinded")]
+    OptionNotFound {},
+
     #[error("expired option (expired {expired:?})")]
     OptionExpired { expired: u64 },
 
     #[error("not expired option (expires {expires:?})")]
     OptionNotExpired { expires: u64 },
 
-    #[error("unauthorized")]
-    Unauthorized {},
+    #[error("Unauthorized for action: {action}")]
+    Unauthorized { action: String },
 
     #[error("must send exact counter offer (offer {offer:?}, counter_offer: {counter_offer:?})")]
     CounterOfferMismatch {
@@ -21,6 +30,226 @@ pub enum ContractError {
         counter_offer: Vec<Coin>,
     },
 
+    #[error("counter offer underpaid, paid in denoms it does not ask for: {denoms:?}")]
+    UnexpectedDenoms { denoms: Vec<String> },
+
+    #[error("paid {extra}{denom} over the counter offer, more than max_extra {max_extra}{denom}")]
+    ExceedsTolerance {
+        denom: String,
+        extra: Uint128,
+        max_extra: Uint128,
+    },
+
+    #[error("execute deadline {deadline} exceeded")]
+    DeadlineExceeded { deadline: u64 },
+
     #[error("do not send funds with burn")]
     FundsSentWithBurn {},
+
+    #[error("collateral and counter_offer are the same coins")]
+    DegenerateOption {},
+
+    #[error("collateral and counter_offer overlap on denom {denom}")]
+    OverlappingDenoms { denom: String },
+
+    #[error("contract balance does not cover the collateral in {denom}")]
+    InsufficientContractBalance { denom: String },
+
+    #[error("option is already held by its creator")]
+    NothingToRenounce {},
+
+    #[error("recipient {recipient} is not whitelisted")]
+    RecipientNotWhitelisted { recipient: String },
+
+    #[error("zero amount coin in {denom}")]
+    ZeroAmountCoin { denom: String },
+
+    #[error("coins must not be empty")]
+    EmptyCoins {},
+
+    #[error("duplicate denom {denom}")]
+    DuplicateDenom { denom: String },
+
+    #[error("cash settled options need collateral in a single denom, without an NFT")]
+    CashSettledCollateral {},
+
+    #[error("option is out of the money (price {price}, strike {strike})")]
+    OutOfTheMoney { price: Uint128, strike: Uint128 },
+
+    #[error("nothing to sweep in {denom}")]
+    NothingToSweep { denom: String },
+
+    #[error("CW20 counter_offer transfer failed (missing allowance?): {error}")]
+    Cw20TransferFailed { error: String },
+
+    #[error("exercise hook failed: {error}")]
+    HookFailed { error: String },
+
+    #[error("unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("address {address} is blocked")]
+    AddressBlocked { address: String },
+
+    #[error("stored address {address} no longer validates")]
+    InvalidStoredAddress { address: String },
+
+    #[error("invalid address {input}: {source}")]
+    InvalidAddress { input: String, source: StdError },
+
+    #[error("payout splits must be non-zero bps summing to 10000, without receive_as")]
+    InvalidSplits {},
+
+    #[error("co-owners must be non-zero bps summing to 10000, other than the creator, physically settled without NFT or settlement delay")]
+    InvalidOwners {},
+
+    #[error("a co-owned option cannot change hands or redirect its collateral")]
+    CoOwned {},
+
+    #[error("exercise would emit {count} messages, more than max_submsgs {max}")]
+    TooManyMessages { count: usize, max: u32 },
+
+    #[error("execute re-entered while an exercise is in progress")]
+    Reentrancy {},
+
+    #[error("no pending collateral release")]
+    NoPendingRelease {},
+
+    #[error("collateral is released at height {release_height}")]
+    ReleaseNotReady { release_height: u64 },
+
+    #[error("no rescind proposal to accept")]
+    NoRescindProposal {},
+
+    #[error("rescind must be accepted by the other party")]
+    CannotSelfAccept {},
+
+    #[error("no admin proposal to accept")]
+    NoAdminProposal {},
+
+    #[error("malformed IBC denom: {denom}")]
+    InvalidIbcDenom { denom: String },
+
+    #[error("option lifetime too short (min {min} blocks)")]
+    LifetimeTooShort { min: u64 },
+
+    #[error("expiry too far in the future (max {max} blocks)")]
+    ExpiryTooFar { max: u64 },
+
+    #[error("overflow in amount calculation")]
+    Overflow {},
+
+    #[error("unexpected NFT contract {contract}")]
+    UnexpectedNftContract { contract: String },
+
+    #[error("NFT collateral already received")]
+    NftAlreadyReceived {},
+
+    #[error("NFT collateral not received yet")]
+    NftNotReceived {},
+
+    #[error("option was already transferred by its creator")]
+    AlreadyTransferred {},
+
+    #[error("counter_offer must not be empty")]
+    EmptyCounterOffer {},
+
+    #[error("denom {denom} is not allowed")]
+    DenomNotAllowed { denom: String },
+
+    #[error("no approved spender to cancel")]
+    NoApproval {},
+
+    #[error("collateral below expected in {denom}: expected {expected}, held {held}")]
+    CollateralBelowExpected {
+        denom: String,
+        expected: Uint128,
+        held: Uint128,
+    },
+
+    #[error("option terms changed, counter_offer is now {counter_offer:?}")]
+    StateChanged { counter_offer: Vec<Coin> },
+
+    #[error("the creator may not exercise their own option")]
+    CreatorCannotExercise {},
+
+    #[error("option is paused")]
+    OptionPaused {},
+
+    #[error("withdraw window not open until height {opens}")]
+    WithdrawWindowNotOpen { opens: u64 },
+
+    #[error("no counter_offer attached or escrowed")]
+    NoEscrow {},
+
+    #[error("no escrowed counter_offer to claim")]
+    NothingToClaim {},
+
+    #[error("a cash settled option has no counter offer to deposit")]
+    NoCounterOffer {},
+
+    #[error("NFT collateral cannot be held back by settlement_delay_blocks")]
+    DelayedNftCollateral {},
+
+    #[error("a cash settled payout cannot be held back by settlement_delay_blocks")]
+    DelayedCashSettlement {},
+
+    #[error("holder rebate of {bps} bps would leave nothing to the creator")]
+    RebateTooHigh { bps: u16 },
+
+    #[error(
+        "referral of {bps} bps on top of the holder rebate would leave nothing to the creator"
+    )]
+    ReferralTooHigh { bps: u16 },
+
+    #[error("{referrer} cannot take the referral fee of its own exercise")]
+    SelfReferral { referrer: String },
+
+    #[error("collar floor of {denom} above its cap")]
+    InvalidCollar { denom: String },
+
+    #[error("decaying counter_offer needs a single denom and a non-zero min_counter_offer")]
+    DecayCounterOffer {},
+
+    #[error("{field} longer than {max} characters")]
+    LabelTooLong { field: String, max: usize },
+
+    #[error("burn reward is not covered by the collateral")]
+    BurnRewardExceedsCollateral {},
+
+    #[error("zero ratio from {from} to {to}")]
+    ZeroRatio { from: String, to: String },
+
+    #[error("receive_as needs a router to swap the counter_offer through")]
+    MissingRouter {},
+
+    #[error("expiry extension is not offered for this option")]
+    ExtensionDisabled {},
+
+    #[error("must send exact extension fee (paid {paid:?}, fee: {fee:?})")]
+    ExtensionFeeMismatch { paid: Vec<Coin>, fee: Vec<Coin> },
+
+    #[error("new expiry must be after the current one ({expires})")]
+    ExpiryNotIncreased { expires: u64 },
+
+    #[error("collateral in {denom} below minimum (required {required}, provided {provided})")]
+    CollateralBelowMinimum {
+        denom: String,
+        required: Uint128,
+        provided: Uint128,
+    },
+}
+
+/*
+constructors for map_err at storage call sites, so a failure names the operation instead of surfacing
+as a bare StdError
+*/
+impl ContractError {
+    pub fn storage_load(source: StdError) -> Self {
+        ContractError::StorageLoad { source }
+    }
+
+    pub fn storage_save(source: StdError) -> Self {
+        ContractError::StorageSave { source }
+    }
 }
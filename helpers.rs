@@ -0,0 +1,115 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Coin, CosmosMsg, CustomQuery, QuerierWrapper, StdResult, WasmMsg,
+};
+
+use crate::msg::{ConfigResponse, ExecuteMsg, QueryMsg};
+
+/// OptionContract is a wrapper around Addr that provides typed helpers
+/// for calling and querying an option contract from another contract.
+#[cw_serde]
+pub struct OptionContract(pub Addr);
+
+impl OptionContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
+        self.call_with_funds(msg, vec![])
+    }
+
+    /// Execute needs the counter_offer attached, so funds can be sent along
+    pub fn call_with_funds<T: Into<ExecuteMsg>>(
+        &self,
+        msg: T,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = to_binary(&msg.into())?;
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg,
+            funds,
+        }
+        .into())
+    }
+
+    pub fn config<C: CustomQuery>(&self, querier: &QuerierWrapper<C>) -> StdResult<ConfigResponse> {
+        querier.query_wasm_smart(self.addr(), &QueryMsg::Config {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{instantiate, query};
+    use crate::msg::InstantiateMsg;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockQuerier};
+    use cosmwasm_std::{coins, from_binary, ContractResult, Empty, SystemResult, WasmQuery};
+
+    #[test]
+    fn call_and_config() {
+        let contract = OptionContract(Addr::unchecked("option"));
+
+        let msg = contract
+            .call_with_funds(
+                ExecuteMsg::Execute {
+                    max_extra: None,
+                    deadline: None,
+                    expected_counter_offer: None,
+                    min_collateral_out: None,
+                    referrer: None,
+                },
+                coins(40, "ETH"),
+            )
+            .unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "option".to_string(),
+                msg: to_binary(&ExecuteMsg::Execute {
+                    max_extra: None,
+                    deadline: None,
+                    expected_counter_offer: None,
+                    min_collateral_out: None,
+                    referrer: None,
+                })
+                .unwrap(),
+                funds: coins(40, "ETH"),
+            })
+        );
+
+        // the option as the contract itself reports it
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &coins(1, "BTC")),
+            msg,
+        )
+        .unwrap();
+        let view: ConfigResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+
+        let mut querier = MockQuerier::<Empty>::new(&[]);
+        let response = view.clone();
+        querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "option" => {
+                let msg: QueryMsg = from_binary(msg).unwrap();
+                assert_eq!(msg, QueryMsg::Config {});
+                SystemResult::Ok(ContractResult::Ok(to_binary(&response).unwrap()))
+            }
+            q => panic!("unexpected query: {:?}", q),
+        });
+
+        let config = contract
+            .config(&QuerierWrapper::<Empty>::new(&querier))
+            .unwrap();
+        assert_eq!(config, view);
+    }
+}
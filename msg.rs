@@ -1,31 +1,637 @@
-use crate::state::State;
+use crate::state::{BurnBeneficiary, BurnMode, LastAction, Receipt, Settlement};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Coin;
+use cosmwasm_std::{Coin, Decimal, Uint128};
+use cw721::Cw721ReceiveMsg;
 
 #[cw_serde]
+#[derive(Default)]
 pub struct InstantiateMsg {
     // owner and creator come from env
     // collateral comes from env
     pub counter_offer: Vec<Coin>,
     pub expires: u64,
+    /// Reject options whose collateral and counter_offer share a denom
+    #[serde(default)]
+    pub strict_denoms: bool,
+    /// Restrict transfers to these addresses, unrestricted when None or empty
+    pub transfer_whitelist: Option<Vec<String>>,
+    /// CW721 contract whose NFT the creator posts as (additional) collateral through ReceiveNft
+    pub nft_contract: Option<String>,
+    /// Admin allowed to force expiry in emergencies
+    pub admin: Option<String>,
+    /// Physical (default) or cash settlement against an oracle price
+    #[serde(default)]
+    pub settlement: Settlement,
+    /// Who receives the collateral on burn, the creator by default
+    #[serde(default)]
+    pub burn_beneficiary: BurnBeneficiary,
+    /// Who may burn the expired option, anyone by default
+    #[serde(default)]
+    pub burn_mode: BurnMode,
+    /// Denoms allowed in the collateral and counter_offer, everything is allowed when empty
+    #[serde(default)]
+    pub allowed_denoms: Vec<String>,
+    /// (old, new) denom renames, a counter_offer denom is matched by payments under either name
+    #[serde(default)]
+    pub denom_aliases: Vec<(String, String)>,
+    /// Linear discount of the counter_offer per block in bps, needs a single denom counter_offer
+    #[serde(default)]
+    pub decay_bps_per_block: u16,
+    /// Floor of the decaying counter_offer, required when decay_bps_per_block is set
+    pub min_counter_offer: Option<Uint128>,
+    /// Lower bound per denom of the due counter_offer, at most the cap of the denom
+    #[serde(default)]
+    pub floor: Option<Vec<Coin>>,
+    /// Upper bound per denom of the due counter_offer
+    #[serde(default)]
+    pub cap: Option<Vec<Coin>>,
+    /// Minimum collateral per denom, denoms not listed have no minimum
+    #[serde(default)]
+    pub min_collateral: Vec<Coin>,
+    /// Fee the owner pays the creator to extend expiry, extension is disabled when empty
+    #[serde(default)]
+    pub extension_fee: Vec<Coin>,
+    /// CW20 the creator receives the counter_offer as, swapped through router
+    pub receive_as: Option<String>,
+    /// AMM router used for receive_as
+    pub router: Option<String>,
+    /// (from, to, ratio) - 1 from is worth ratio to, so the whole counter_offer may be paid in to
+    #[serde(default)]
+    pub denom_ratios: Vec<(String, String, Decimal)>,
+    /// Paid out of the collateral to whoever burns the expired option
+    #[serde(default)]
+    pub burn_reward: Vec<Coin>,
+    /// Send every denom in a BankMsg of its own, for chains that dislike multi-coin sends
+    #[serde(default)]
+    pub split_sends: bool,
+    /// Most messages an exercise may emit, bounding its gas - unbounded when not given
+    pub max_submsgs: Option<u32>,
+    /// Contract answering ComplianceQueryMsg::IsBlocked, checked on execute and burn
+    pub compliance_contract: Option<String>,
+    /// Contract notified with HookExecuteMsg::OptionExecuted on exercise, failing the exercise if it fails
+    pub exercise_hook: Option<String>,
+    /// Reject malformed ibc/ denoms in the collateral and counter_offer
+    #[serde(default)]
+    pub validate_ibc_denoms: bool,
+    /// Blocks before expiry from which the creator may withdraw a never transferred option, 0 disables
+    #[serde(default)]
+    pub early_withdraw_window: u64,
+    /// CW20 (token, amount) pulled from the executor through an allowance, on top of the native counter_offer
+    pub cw20_counter_offer: Option<(String, Uint128)>,
+    /// Share of the counter_offer in bps rebated to the executor, below 10000
+    #[serde(default)]
+    pub holder_rebate_bps: u16,
+    /// Share of the counter_offer in bps paid to a referrer named on execute
+    #[serde(default)]
+    pub referral_bps: u16,
+    /// Per denom rounding tolerance, payments within it of the counter_offer are forwarded as sent
+    #[serde(default)]
+    pub tolerance: Vec<Coin>,
+    /// Blocks the coin collateral is held after execution, claimed through ClaimRelease (physical, without an NFT)
+    #[serde(default)]
+    pub settlement_delay_blocks: u64,
+    /// Payees sharing the counter_offer in bps summing to 10000, the creator gets it all when empty
+    #[serde(default)]
+    pub payout_splits: Vec<(String, u16)>,
+    /// Treat an execute attempt past expiry as a burn instead of failing
+    #[serde(default)]
+    pub auto_burn_on_expired: bool,
+    /// Co-owners splitting the collateral in bps summing to 10000, held by the creator alone when empty
+    #[serde(default)]
+    pub owners: Vec<(String, u16)>,
+    /// Short human readable name of the option
+    pub label: Option<String>,
+    /// Free text note stored with the option
+    pub memo: Option<String>,
+    /// Reject options whose collateral equals the counter_offer
+    #[serde(default)]
+    pub reject_degenerate: bool,
+    /// Minimum blocks between instantiation and expiry, 1 when not given
+    pub min_lifetime_blocks: Option<u64>,
+    /// Whether the creator may exercise their own option, true when not given
+    pub allow_creator_exercise: Option<bool>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
     /// Owner can transfer to a new owner
     Transfer { recipient: String },
-    /// Owner can post counter_offer on unexpired option to execute and get the collateral
-    Execute {},
+    /// Owner can post counter_offer on unexpired option to execute and get the collateral,
+    /// paying at most max_extra per denom on top of it when given, failing past the deadline height
+    /// or when the counter_offer differs from expected_counter_offer or the collateral is below min_collateral_out.
+    /// A referrer receives the referral_bps share of the counter_offer
+    Execute {
+        max_extra: Option<Vec<Coin>>,
+        deadline: Option<u64>,
+        expected_counter_offer: Option<Vec<Coin>>,
+        min_collateral_out: Option<Vec<Coin>>,
+        referrer: Option<String>,
+    },
+    /// Like Execute, but the collateral is delivered to recipient instead of the owner
+    ExecuteTo { recipient: String },
+    /// With on_behalf, the approved spender executes for the owner, who still gets the collateral
+    ExecuteFor { on_behalf: bool },
     /// Burn will release collateral if expired
     Burn {},
+    /// Owner gives up the option, handing it back to the creator
+    Renounce {},
+    /// CW721 hook, the creator sends the NFT collateral through the NFT contract
+    ReceiveNft(Cw721ReceiveMsg),
+    /// Owner allows spender to transfer the option on their behalf
+    Approve { spender: String },
+    /// Owner revokes the approved spender, e.g. retracting a marketplace listing
+    CancelApproval {},
+    /// Recipient of a delayed collateral release claims it once the release height is reached
+    ClaimRelease {},
+    /// Creator can recover tokens of denom sent to the contract on top of the collateral
+    Sweep { denom: String },
+    /// Admin marks the option expired so it can be burned right away
+    ForceExpire {},
+    /// Owner or creator proposes to unwind the option before expiry
+    ProposeRescind {},
+    /// The other party accepts, returning the collateral to the creator
+    AcceptRescind {},
+    /// Creator changes the demanded counter_offer while still holding the option
+    UpdateCounterOffer { counter_offer: Vec<Coin> },
+    /// Owner escrows the attached funds towards the counter_offer, Execute then pays out of the escrow
+    DepositCounterOffer {},
+    /// Owner reclaims their escrowed counter_offer once the option expired unexercised
+    ClaimExpired {},
+    /// Owner pays the extension_fee to the creator to push expiry out to new_expires
+    ExtendExpiry { new_expires: u64 },
+    /// Admin proposes new_admin to take over, effective once accepted
+    ProposeAdmin { new_admin: String },
+    /// The proposed admin accepts the handover
+    AcceptAdmin {},
+    /// Creator voids a never transferred option within early_withdraw_window blocks of expiry, a renounced one any time
+    Withdraw {},
+    /// Creator pauses (or resumes) exercising of a never transferred option, burn is unaffected
+    PauseOption { paused: bool },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    /// Economic terms of the option and its status at the current block, without bookkeeping fields
     #[returns(ConfigResponse)]
     Config {},
+    /// Contract name, version and address of this instance
+    #[returns(InfoResponse)]
+    Info {},
+    /// Last executed action, also available once the option was executed or burned
+    #[returns(Option<LastAction>)]
+    LastAction {},
+    /// Receipt of the exercised option id, None while it has not been exercised
+    #[returns(Option<Receipt>)]
+    Receipt { id: u64 },
+    /// Dry run of Execute by sender with funds attached, showing who would get what
+    #[returns(SimulateExecuteResponse)]
+    SimulateExecute { funds: Vec<Coin>, sender: String },
+    /// How pay compares to the counter_offer currently due, per denom
+    #[returns(QuoteResponse)]
+    Quote { pay: Vec<Coin> },
+    /// Pricing summary for UIs - the counter_offer, plus strike and oracle price when cash settled
+    #[returns(BreakEvenResponse)]
+    BreakEven {},
+    /// What the contract holds and expects, cross-checked against its bank balance
+    #[returns(EscrowResponse)]
+    Escrow {},
+    /// Flattened, CSV-friendly record of the option held by this contract (none before instantiation)
+    #[returns(ExportResponse)]
+    Export {},
+}
+
+/// Minimal query interface expected from a price oracle used for cash settlement
+#[cw_serde]
+pub enum OracleQueryMsg {
+    Price {},
+}
+
+#[cw_serde]
+pub struct PriceResponse {
+    /// Price of one unit of the collateral denom, in the same quote units as the strike
+    pub price: Uint128,
+}
+
+/// Minimal query interface expected from a compliance (blacklist) contract
+#[cw_serde]
+pub enum ComplianceQueryMsg {
+    IsBlocked { address: String },
+}
+
+#[cw_serde]
+pub struct IsBlockedResponse {
+    pub blocked: bool,
+}
+
+/// Minimal execute interface expected from an exercise_hook contract
+#[cw_serde]
+pub enum HookExecuteMsg {
+    /// The option was exercised, amount being the counter_offer paid to the creator
+    OptionExecuted {
+        creator: String,
+        owner: String,
+        amount: Vec<Coin>,
+    },
+}
+
+/// Minimal execute interface of a CW20 token, matching cw20::Cw20ExecuteMsg::TransferFrom
+#[cw_serde]
+pub enum Cw20ExecuteMsg {
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+}
+
+/// Minimal execute interface expected from an AMM router used for receive_as
+#[cw_serde]
+pub enum RouterExecuteMsg {
+    /// Swap the attached native funds into the ask_token CW20 and send it to recipient
+    Swap {
+        ask_token: String,
+        recipient: String,
+    },
 }
 
 // We define a custom struct for each query response
-pub type ConfigResponse = State;
+pub type ConfigResponse = StateView;
+
+/// Data set on the execute and burn responses, describing the funds released
+#[cw_serde]
+pub struct ExecuteResult {
+    /// Counter offer forwarded to the creator, empty on burn
+    pub counter_offer: Vec<Coin>,
+    /// Collateral released by the contract
+    pub collateral: Vec<Coin>,
+    /// Address the collateral was sent to
+    pub collateral_recipient: String,
+}
+
+#[cw_serde]
+pub struct SimulateExecuteResponse {
+    pub ok: bool,
+    /// Why execution would fail, if it would
+    pub error: Option<String>,
+    /// (recipient, amount) of every bank send and funded contract call, in message order
+    pub would_send: Vec<(String, Vec<Coin>)>,
+}
+
+#[cw_serde]
+pub struct InfoResponse {
+    pub contract: String,
+    pub version: String,
+    pub address: String,
+}
+
+#[cw_serde]
+pub struct StateView {
+    pub creator: String,
+    pub owner: String,
+    pub collateral: Vec<Coin>,
+    pub counter_offer: Vec<Coin>,
+    pub expires: u64,
+    pub settlement: Settlement,
+    /// NFT posted as collateral, as (contract, token_id)
+    pub nft_collateral: Option<(String, String)>,
+    /// Counter offer held on behalf of the owner ahead of execution
+    pub escrow: Vec<Coin>,
+    pub status: OptionStatus,
+}
+
+#[cw_serde]
+pub struct QuoteResponse {
+    /// Whether pay covers the counter_offer, i.e. shortfall is empty
+    pub sufficient: bool,
+    /// Missing amount per denom
+    pub shortfall: Vec<Coin>,
+    /// Amount per denom paid on top of the counter_offer, refunded on execution
+    pub excess: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct BreakEvenResponse {
+    /// The stored counter_offer
+    pub required: Vec<Coin>,
+    /// Strike of a cash settled option, None when physically settled
+    pub strike: Option<Uint128>,
+    /// Oracle price of a cash settled option, None when physically settled
+    pub current_price: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct EscrowResponse {
+    pub collateral: Vec<Coin>,
+    pub counter_offer: Vec<Coin>,
+    /// Whether the contract's bank balance equals the recorded collateral plus any escrowed counter_offer
+    pub balance_matches: bool,
+}
+
+#[cw_serde]
+pub enum OptionStatus {
+    /// Still held by the creator and not expired
+    Open,
+    /// Held by someone other than the creator and not expired
+    Transferred,
+    /// Exercising paused by the creator, not expired
+    Paused,
+    /// Can no longer be executed, only burned
+    Expired,
+}
+
+#[cw_serde]
+pub struct ExportRecord {
+    pub id: u64,
+    pub creator: String,
+    pub owner: String,
+    pub expires: u64,
+    /// Comma separated coins, e.g. "1BTC,40ETH"
+    pub collateral_string: String,
+    /// Comma separated coins, e.g. "1BTC,40ETH"
+    pub counter_offer_string: String,
+    pub status: OptionStatus,
+}
+
+#[cw_serde]
+pub struct ExportResponse {
+    pub options: Vec<ExportRecord>,
+}
+
+#[cfg(test)]
+mod schema_tests {
+    use super::*;
+
+    use crate::state::State;
+    use cosmwasm_schema::serde::de::DeserializeOwned;
+    use cosmwasm_schema::serde::Serialize;
+    use cosmwasm_std::{coins, from_slice, to_vec, Addr, Binary};
+    use std::fmt::Debug;
+
+    // serializes msg the way the chain does, checks it parses back unchanged and returns the json
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(msg: &T) -> String {
+        let json = to_vec(msg).unwrap();
+        let parsed: T = from_slice(&json).unwrap();
+        assert_eq!(&parsed, msg);
+        String::from_utf8(json).unwrap()
+    }
+
+    #[test]
+    fn instantiate_msg() {
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            strict_denoms: true,
+            transfer_whitelist: Some(vec!["buyer".to_string()]),
+            nft_contract: Some("nft".to_string()),
+            admin: Some("admin".to_string()),
+            settlement: Settlement::CashSettled {
+                oracle: Addr::unchecked("oracle"),
+                strike: Uint128::new(100),
+            },
+            burn_beneficiary: BurnBeneficiary::Owner,
+            burn_mode: BurnMode::CreatorOnly,
+            allowed_denoms: vec!["ETH".to_string()],
+            denom_aliases: vec![("uatom".to_string(), "ibc/ATOM".to_string())],
+            decay_bps_per_block: 10,
+            min_counter_offer: Some(Uint128::new(20)),
+            floor: Some(coins(30, "ETH")),
+            cap: Some(coins(50, "ETH")),
+            min_collateral: coins(1, "BTC"),
+            extension_fee: coins(1, "ETH"),
+            receive_as: Some("cw20".to_string()),
+            router: Some("router".to_string()),
+            denom_ratios: vec![("ATOM".to_string(), "ETH".to_string(), Decimal::percent(25))],
+            burn_reward: coins(1, "BTC"),
+            split_sends: true,
+            max_submsgs: Some(8),
+            compliance_contract: Some("compliance".to_string()),
+            exercise_hook: Some("hook".to_string()),
+            validate_ibc_denoms: true,
+            early_withdraw_window: 100,
+            cw20_counter_offer: Some(("cw20".to_string(), Uint128::new(10))),
+            holder_rebate_bps: 1_000,
+            referral_bps: 500,
+            tolerance: coins(1, "ETH"),
+            settlement_delay_blocks: 10,
+            payout_splits: vec![("alice".to_string(), 7_000), ("bob".to_string(), 3_000)],
+            auto_burn_on_expired: true,
+            owners: vec![("alice".to_string(), 5_000), ("bob".to_string(), 5_000)],
+            label: Some("label".to_string()),
+            memo: None,
+            reject_degenerate: true,
+            min_lifetime_blocks: Some(10),
+            allow_creator_exercise: Some(false),
+        };
+        let json = round_trip(&msg);
+        assert!(
+            json.contains(r#""settlement":{"cash_settled":{"oracle":"oracle","strike":"100"}}"#)
+        );
+        assert!(json.contains(r#""burn_beneficiary":"owner""#));
+        assert!(json.contains(r#""burn_mode":"creator_only""#));
+
+        // messages written before max_extra existed still parse
+        let msg: ExecuteMsg = from_slice(br#"{"execute":{}}"#).unwrap();
+        assert_eq!(
+            msg,
+            ExecuteMsg::Execute {
+                max_extra: None,
+                deadline: None,
+                expected_counter_offer: None,
+                min_collateral_out: None,
+                referrer: None,
+            }
+        );
+
+        // optional fields may be left out entirely
+        let msg: InstantiateMsg = from_slice(br#"{"counter_offer":[],"expires":5}"#).unwrap();
+        assert_eq!(msg.settlement, Settlement::Physical);
+        assert_eq!(msg.burn_beneficiary, BurnBeneficiary::Creator);
+        assert_eq!(msg.burn_mode, BurnMode::Anyone);
+    }
+
+    #[test]
+    fn execute_msg() {
+        let cases = [
+            (
+                ExecuteMsg::Transfer {
+                    recipient: "buyer".to_string(),
+                },
+                r#"{"transfer":{"recipient":"buyer"}}"#,
+            ),
+            (
+                ExecuteMsg::Execute {
+                    max_extra: None,
+                    deadline: None,
+                    expected_counter_offer: None,
+                    min_collateral_out: None,
+                    referrer: None,
+                },
+                r#"{"execute":{"max_extra":null,"deadline":null,"expected_counter_offer":null,"min_collateral_out":null,"referrer":null}}"#,
+            ),
+            (
+                ExecuteMsg::Execute {
+                    max_extra: Some(coins(1, "ETH")),
+                    deadline: Some(5),
+                    expected_counter_offer: Some(coins(40, "ETH")),
+                    min_collateral_out: Some(coins(1, "BTC")),
+                    referrer: Some("referrer".to_string()),
+                },
+                r#"{"execute":{"max_extra":[{"denom":"ETH","amount":"1"}],"deadline":5,"expected_counter_offer":[{"denom":"ETH","amount":"40"}],"min_collateral_out":[{"denom":"BTC","amount":"1"}],"referrer":"referrer"}}"#,
+            ),
+            (
+                ExecuteMsg::ExecuteTo {
+                    recipient: "buyer".to_string(),
+                },
+                r#"{"execute_to":{"recipient":"buyer"}}"#,
+            ),
+            (
+                ExecuteMsg::ExecuteFor { on_behalf: true },
+                r#"{"execute_for":{"on_behalf":true}}"#,
+            ),
+            (ExecuteMsg::Burn {}, r#"{"burn":{}}"#),
+            (ExecuteMsg::Renounce {}, r#"{"renounce":{}}"#),
+            (
+                ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                    sender: "creator".to_string(),
+                    token_id: "1".to_string(),
+                    msg: Binary::default(),
+                }),
+                r#"{"receive_nft":{"sender":"creator","token_id":"1","msg":""}}"#,
+            ),
+            (
+                ExecuteMsg::Approve {
+                    spender: "spender".to_string(),
+                },
+                r#"{"approve":{"spender":"spender"}}"#,
+            ),
+            (ExecuteMsg::CancelApproval {}, r#"{"cancel_approval":{}}"#),
+            (ExecuteMsg::ClaimRelease {}, r#"{"claim_release":{}}"#),
+            (
+                ExecuteMsg::Sweep {
+                    denom: "ETH".to_string(),
+                },
+                r#"{"sweep":{"denom":"ETH"}}"#,
+            ),
+            (ExecuteMsg::ForceExpire {}, r#"{"force_expire":{}}"#),
+            (ExecuteMsg::ProposeRescind {}, r#"{"propose_rescind":{}}"#),
+            (ExecuteMsg::AcceptRescind {}, r#"{"accept_rescind":{}}"#),
+            (
+                ExecuteMsg::UpdateCounterOffer {
+                    counter_offer: coins(40, "ETH"),
+                },
+                r#"{"update_counter_offer":{"counter_offer":[{"denom":"ETH","amount":"40"}]}}"#,
+            ),
+            (
+                ExecuteMsg::DepositCounterOffer {},
+                r#"{"deposit_counter_offer":{}}"#,
+            ),
+            (ExecuteMsg::ClaimExpired {}, r#"{"claim_expired":{}}"#),
+            (
+                ExecuteMsg::ExtendExpiry { new_expires: 5 },
+                r#"{"extend_expiry":{"new_expires":5}}"#,
+            ),
+            (
+                ExecuteMsg::ProposeAdmin {
+                    new_admin: "admin".to_string(),
+                },
+                r#"{"propose_admin":{"new_admin":"admin"}}"#,
+            ),
+            (ExecuteMsg::AcceptAdmin {}, r#"{"accept_admin":{}}"#),
+            (ExecuteMsg::Withdraw {}, r#"{"withdraw":{}}"#),
+            (
+                ExecuteMsg::PauseOption { paused: true },
+                r#"{"pause_option":{"paused":true}}"#,
+            ),
+        ];
+        for (msg, expected) in cases {
+            assert_eq!(round_trip(&msg), expected);
+        }
+    }
+
+    #[test]
+    fn query_msg() {
+        let cases = [
+            (QueryMsg::Config {}, r#"{"config":{}}"#),
+            (QueryMsg::Info {}, r#"{"info":{}}"#),
+            (QueryMsg::LastAction {}, r#"{"last_action":{}}"#),
+            (QueryMsg::Receipt { id: 0 }, r#"{"receipt":{"id":0}}"#),
+            (
+                QueryMsg::SimulateExecute {
+                    funds: coins(40, "ETH"),
+                    sender: "owner".to_string(),
+                },
+                r#"{"simulate_execute":{"funds":[{"denom":"ETH","amount":"40"}],"sender":"owner"}}"#,
+            ),
+            (
+                QueryMsg::Quote {
+                    pay: coins(40, "ETH"),
+                },
+                r#"{"quote":{"pay":[{"denom":"ETH","amount":"40"}]}}"#,
+            ),
+            (QueryMsg::BreakEven {}, r#"{"break_even":{}}"#),
+            (QueryMsg::Escrow {}, r#"{"escrow":{}}"#),
+            (QueryMsg::Export {}, r#"{"export":{}}"#),
+        ];
+        for (msg, expected) in cases {
+            assert_eq!(round_trip(&msg), expected);
+        }
+    }
+
+    #[test]
+    fn state() {
+        let state = State {
+            creator: Addr::unchecked("creator"),
+            owner: Addr::unchecked("owner"),
+            collateral: coins(1, "BTC"),
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            transfer_whitelist: None,
+            settlement: Settlement::Physical,
+            burn_beneficiary: BurnBeneficiary::Creator,
+            burn_mode: BurnMode::Anyone,
+            approved: Some(Addr::unchecked("spender")),
+            admin: None,
+            nft_contract: None,
+            nft_collateral: Some((Addr::unchecked("nft"), "1".to_string())),
+            escrow: vec![],
+            decay_bps_per_block: 0,
+            decay_start: 0,
+            min_counter_offer: None,
+            floor: None,
+            cap: None,
+            extension_fee: vec![],
+            receive_as: None,
+            router: None,
+            denom_ratios: vec![],
+            burn_reward: vec![],
+            split_sends: false,
+            max_submsgs: None,
+            compliance_contract: None,
+            exercise_hook: None,
+            validate_ibc_denoms: false,
+            early_withdraw_window: 0,
+            cw20_counter_offer: None,
+            holder_rebate_bps: 0,
+            referral_bps: 0,
+            tolerance: vec![],
+            settlement_delay_blocks: 0,
+            payout_splits: vec![],
+            auto_burn_on_expired: false,
+            owners: vec![],
+            allow_creator_exercise: true,
+            paused: false,
+            renounced: false,
+            label: None,
+            memo: None,
+        };
+        let json = round_trip(&state);
+        assert!(json.contains(r#""settlement":"physical""#));
+        assert!(json.contains(r#""nft_collateral":["nft","1"]"#));
+
+        let status = round_trip(&OptionStatus::Transferred);
+        assert_eq!(status, r#""transferred""#);
+        let status = round_trip(&OptionStatus::Paused);
+        assert_eq!(status, r#""paused""#);
+    }
+}
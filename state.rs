@@ -1,7 +1,7 @@
 use cosmwasm_schema::cw_serde;
 
-use cosmwasm_std::{Addr, Coin};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
+use cw_storage_plus::{Item, Map};
 
 #[cw_serde]
 pub struct State {
@@ -10,7 +10,199 @@ pub struct State {
     pub collateral: Vec<Coin>,
     pub counter_offer: Vec<Coin>,
     pub expires: u64,
+    pub transfer_whitelist: Option<Vec<Addr>>,
+    #[serde(default)]
+    pub settlement: Settlement,
+    #[serde(default)]
+    pub burn_beneficiary: BurnBeneficiary,
+    #[serde(default)]
+    pub burn_mode: BurnMode,
+    /// Spender allowed to transfer the option on the owner's behalf
+    pub approved: Option<Addr>,
+    /// May force expiry of the option in emergencies
+    pub admin: Option<Addr>,
+    /// CW721 contract expected to deliver the NFT collateral
+    pub nft_contract: Option<Addr>,
+    /// NFT posted as collateral, as (contract, token_id)
+    pub nft_collateral: Option<(Addr, String)>,
+    /// Counter offer held on behalf of the owner ahead of execution, reclaimable once expired
+    #[serde(default)]
+    pub escrow: Vec<Coin>,
+    /// Required counter_offer shrinks by this many bps of itself every block since decay_start
+    #[serde(default)]
+    pub decay_bps_per_block: u16,
+    /// Height the decay is counted from, the instantiation height
+    #[serde(default)]
+    pub decay_start: u64,
+    /// Amount the decaying counter_offer never goes below
+    pub min_counter_offer: Option<Uint128>,
+    /// Per denom bounds (collar) the due counter_offer is clamped into, after any decay
+    #[serde(default)]
+    pub floor: Option<Vec<Coin>>,
+    #[serde(default)]
+    pub cap: Option<Vec<Coin>>,
+    /// Paid by the owner to the creator for every expiry extension, empty disables extension
+    #[serde(default)]
+    pub extension_fee: Vec<Coin>,
+    /// CW20 the creator wants the counter_offer swapped into on execute
+    pub receive_as: Option<Addr>,
+    /// AMM router swapping the counter_offer into receive_as
+    pub router: Option<Addr>,
+    /// (from, to, ratio) - 1 from is worth ratio to, lets the counter_offer be paid entirely in to
+    #[serde(default)]
+    pub denom_ratios: Vec<(String, String, Decimal)>,
+    /// Part of the collateral paid to whoever burns the expired option
+    #[serde(default)]
+    pub burn_reward: Vec<Coin>,
+    /// Send every denom in a BankMsg of its own
+    #[serde(default)]
+    pub split_sends: bool,
+    /// Most messages an exercise may emit (sends, payout splits, hooks), unbounded when None
+    #[serde(default)]
+    pub max_submsgs: Option<u32>,
+    /// Asked whether the creator or owner is blocked before paying out
+    pub compliance_contract: Option<Addr>,
+    /// Contract notified with HookExecuteMsg::OptionExecuted whenever the option is exercised
+    pub exercise_hook: Option<Addr>,
+    /// ibc/ denoms of the collateral and counter_offer must be well formed IBC hashes
+    #[serde(default)]
+    pub validate_ibc_denoms: bool,
+    /// Blocks before expiry from which the creator may withdraw a never transferred option
+    #[serde(default)]
+    pub early_withdraw_window: u64,
+    /// CW20 part of the counter_offer as (token, amount), pulled from the executor's allowance
+    pub cw20_counter_offer: Option<(Addr, Uint128)>,
+    /// Share of the counter_offer in bps sent back to the executor instead of the creator
+    #[serde(default)]
+    pub holder_rebate_bps: u16,
+    /// Share of the counter_offer in bps sent to the referrer named by the executor, instead of the creator
+    #[serde(default)]
+    pub referral_bps: u16,
+    /// Per denom rounding tolerance of the counter_offer, payments within it are forwarded as sent
+    #[serde(default)]
+    pub tolerance: Vec<Coin>,
+    /// Blocks the coin collateral is held after execution before its recipient can claim it
+    #[serde(default)]
+    pub settlement_delay_blocks: u64,
+    /// Payees sharing the counter_offer in bps summing to 10000 instead of the creator, e.g. royalties
+    #[serde(default)]
+    pub payout_splits: Vec<(Addr, u16)>,
+    /// Executing an expired option burns it instead of failing, refunding the attached funds
+    #[serde(default)]
+    pub auto_burn_on_expired: bool,
+    /// Co-owners splitting the collateral in bps summing to 10000, any of them may execute.
+    /// Empty for a single owner, otherwise owner is the first of them
+    #[serde(default)]
+    pub owners: Vec<(Addr, u16)>,
+    /// Whether the creator may exercise their own option, false preventing wash trades
+    #[serde(default = "default_true")]
+    pub allow_creator_exercise: bool,
+    /// Exercising is rejected while paused by the creator
+    #[serde(default)]
+    pub paused: bool,
+    /// Handed back to the creator through Renounce, who may then withdraw outside early_withdraw_window
+    #[serde(default)]
+    pub renounced: bool,
+    /// Free text annotations, e.g. for OTC desks
+    pub label: Option<String>,
+    pub memo: Option<String>,
+}
+
+// options stored before allow_creator_exercise existed let the creator exercise
+fn default_true() -> bool {
+    true
+}
+
+/// How an executed option is settled
+#[cw_serde]
+#[derive(Default)]
+pub enum Settlement {
+    /// Counter offer is swapped for the collateral
+    #[default]
+    Physical,
+    /// Holder receives the amount by which the oracle price exceeds the strike on every unit
+    /// of a single-denom collateral, paid out in that collateral at the oracle price
+    CashSettled { oracle: Addr, strike: Uint128 },
+}
+
+/// Most recent successful execute message, kept after the option itself is removed
+#[cw_serde]
+pub struct LastAction {
+    /// The "action" attribute of the response
+    pub action: String,
+    pub height: u64,
+    pub sender: Addr,
+}
+
+/// Record of an exercise, kept after the option itself is removed for accounting and disputes
+#[cw_serde]
+pub struct Receipt {
+    pub option_id: u64,
+    pub exerciser: Addr,
+    /// Counter offer paid for the collateral
+    pub paid: Vec<Coin>,
+    /// Collateral released for it
+    pub received: Vec<Coin>,
+    pub height: u64,
+}
+
+/// Coin collateral of an executed option held back by settlement_delay_blocks
+#[cw_serde]
+pub struct PendingRelease {
+    pub recipient: Addr,
+    pub amount: Vec<Coin>,
+    /// First height the recipient can claim at
+    pub release_height: u64,
+}
+
+/// Who receives the collateral when an expired option is burned
+#[cw_serde]
+#[derive(Default)]
+pub enum BurnBeneficiary {
+    /// The writer of the option
+    #[default]
+    Creator,
+    /// Whoever holds the option at expiry
+    Owner,
+}
+
+/// Who may burn an expired option
+#[cw_serde]
+#[derive(Default)]
+pub enum BurnMode {
+    /// Any address, e.g. a keeper cleaning up expired options
+    #[default]
+    Anyone,
+    /// Only the writer of the option
+    CreatorOnly,
 }
 
 pub const CONFIG_KEY: &str = "config";
 pub const CONFIG: Item<State> = Item::new(CONFIG_KEY);
+
+/// Party that proposed to rescind the option, waiting for the other party to accept
+pub const RESCIND: Item<Addr> = Item::new("rescind");
+
+/// Admin proposed by the current admin, taking over once they accept
+pub const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
+
+/// Denoms the option may be written in, set once at instantiate - empty allows every denom
+pub const ALLOWED_DENOMS: Item<Vec<String>> = Item::new("allowed_denoms");
+
+/// Old to new denom renames of a chain migrating denoms, a counter_offer denom may be paid under either name.
+/// Set once at instantiate
+pub const DENOM_ALIASES: Item<Vec<(String, String)>> = Item::new("denom_aliases");
+
+/// Minimum collateral per denom, set once at instantiate
+pub const MIN_COLLATERAL: Item<Vec<Coin>> = Item::new("min_collateral");
+
+pub const LAST_ACTION: Item<LastAction> = Item::new("last_action");
+
+/// Receipt of every exercised option, by option id
+pub const RECEIPTS: Map<u64, Receipt> = Map::new("receipts");
+
+/// Set while the exercise_hook of an exercise is pending, until its reply - a hook re-entering execute is rejected
+pub const EXECUTING: Item<bool> = Item::new("executing");
+
+/// Collateral waiting for ExecuteMsg::ClaimRelease, kept after the option itself is removed
+pub const PENDING_RELEASE: Item<PendingRelease> = Item::new("pending_release");
@@ -0,0 +1,154 @@
+/*
+coin vector invariants shared by instantiate and the execute paths - each check returns the first offending
+denom in its error, so the user knows which coin to fix
+*/
+
+use std::collections::BTreeSet;
+
+use cosmwasm_std::Coin;
+
+use crate::error::ContractError;
+
+pub fn non_empty(coins: &[Coin]) -> Result<(), ContractError> {
+    if coins.is_empty() {
+        return Err(ContractError::EmptyCoins {});
+    }
+    Ok(())
+}
+
+/*
+rejects any coin with a zero amount, which some SDKs allow to be sent
+*/
+pub fn no_zero_amounts(coins: &[Coin]) -> Result<(), ContractError> {
+    match coins.iter().find(|c| c.amount.is_zero()) {
+        Some(coin) => Err(ContractError::ZeroAmountCoin {
+            denom: coin.denom.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+pub fn no_duplicate_denoms(coins: &[Coin]) -> Result<(), ContractError> {
+    let mut seen = BTreeSet::new();
+    match coins.iter().find(|c| !seen.insert(c.denom.as_str())) {
+        Some(coin) => Err(ContractError::DuplicateDenom {
+            denom: coin.denom.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+// every coin must be in one of allowed denoms, an empty allowlist allows everything
+pub fn all_allowed(coins: &[Coin], allowed: &[String]) -> Result<(), ContractError> {
+    if allowed.is_empty() {
+        return Ok(());
+    }
+    match coins.iter().find(|c| !allowed.contains(&c.denom)) {
+        Some(coin) => Err(ContractError::DenomNotAllowed {
+            denom: coin.denom.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/*
+denoms starting with ibc/ must be a well formed IBC hash - ibc/ followed by 64 uppercase hex characters,
+catching copy-paste errors in the denom trace hash
+*/
+pub fn ibc_denoms(coins: &[Coin]) -> Result<(), ContractError> {
+    let malformed = |denom: &str| match denom.strip_prefix("ibc/") {
+        Some(hash) => {
+            hash.len() != 64
+                || !hash
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || ('A'..='F').contains(&c))
+        }
+        None => false,
+    };
+    match coins.iter().find(|c| malformed(&c.denom)) {
+        Some(coin) => Err(ContractError::InvalidIbcDenom {
+            denom: coin.denom.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::{coin, coins};
+
+    #[test]
+    fn empty() {
+        assert!(matches!(
+            non_empty(&[]).unwrap_err(),
+            ContractError::EmptyCoins {}
+        ));
+        non_empty(&coins(1, "ETH")).unwrap();
+    }
+
+    #[test]
+    fn zero_amounts() {
+        no_zero_amounts(&[]).unwrap();
+        no_zero_amounts(&[coin(1, "ETH"), coin(2, "BTC")]).unwrap();
+
+        // a single zero coin
+        match no_zero_amounts(&coins(0, "ETH")).unwrap_err() {
+            ContractError::ZeroAmountCoin { denom } => assert_eq!(denom, "ETH"),
+            e => panic!("unexpected error: {}", e),
+        }
+        // zero coin among non-zero ones
+        match no_zero_amounts(&[coin(1, "ETH"), coin(0, "BTC")]).unwrap_err() {
+            ContractError::ZeroAmountCoin { denom } => assert_eq!(denom, "BTC"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn duplicate_denoms() {
+        no_duplicate_denoms(&[]).unwrap();
+        no_duplicate_denoms(&[coin(1, "ETH"), coin(1, "BTC")]).unwrap();
+
+        match no_duplicate_denoms(&[coin(1, "ETH"), coin(1, "BTC"), coin(2, "ETH")]).unwrap_err() {
+            ContractError::DuplicateDenom { denom } => assert_eq!(denom, "ETH"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn allowed() {
+        let allowed = vec!["ETH".to_string()];
+
+        // empty allowlist allows everything
+        all_allowed(&coins(1, "DOGE"), &[]).unwrap();
+        all_allowed(&[], &allowed).unwrap();
+        all_allowed(&coins(1, "ETH"), &allowed).unwrap();
+
+        match all_allowed(&[coin(1, "ETH"), coin(1, "DOGE")], &allowed).unwrap_err() {
+            ContractError::DenomNotAllowed { denom } => assert_eq!(denom, "DOGE"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn ibc() {
+        let valid = format!(
+            "ibc/{}",
+            "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+        ibc_denoms(&[coin(1, &valid), coin(1, "uatom")]).unwrap();
+
+        // too short, lowercase hex and a non hex character are all malformed
+        for denom in [
+            "ibc/27394FB092D2ECCD".to_string(),
+            valid.to_lowercase(),
+            valid.replace('B', "G"),
+        ] {
+            match ibc_denoms(&[coin(1, "uatom"), coin(1, &denom)]).unwrap_err() {
+                ContractError::InvalidIbcDenom { denom: d } => assert_eq!(d, denom),
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+    }
+}